@@ -0,0 +1,317 @@
+use std::ffi::OsString;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use anyhow::{bail, Context, Result};
+
+use crate::hooks::{self, Hook};
+use crate::lock;
+use crate::plan::{Action, Plan};
+use crate::protocol::{self, alternate, is_access_error, Fallback};
+
+/// Carry out the parts of a plan that gg handles itself rather than leaving
+/// to the shell. Clones and fetches of the same repo are serialized across
+/// gg processes.
+pub fn apply(plan: &str) -> Result<()> {
+    let plan = Plan::parse(plan)?;
+    let lock = lock::lock(&plan.repo_dir)?;
+    let action = match plan.action {
+        // Another gg may have finished cloning while we waited for the lock.
+        Action::Clone if plan.repo_dir.is_dir() && !incomplete_clone(&plan.repo_dir) => {
+            eprintln!("{} was cloned meanwhile, fetching", plan.repo_dir.display());
+            fetch(&plan.repo_dir, plan.ssh_command.as_deref())?;
+            Action::Fetch
+        }
+        Action::Clone => {
+            let url = plan
+                .git_url
+                .as_deref()
+                .context("clone plan has no git_url")?;
+            clone(url, &plan.repo_dir, plan.ssh_command.as_deref())?;
+            Action::Clone
+        }
+        Action::Fetch => {
+            fetch(&plan.repo_dir, plan.ssh_command.as_deref())?;
+            Action::Fetch
+        }
+    };
+    // Hooks may take a while, and needn't hold up other gg's.
+    drop(lock);
+
+    let hook = match action {
+        Action::Clone => Hook::PostClone,
+        Action::Fetch => Hook::PostFetch,
+    };
+    hooks::run(hook, &plan, action)?;
+    // The shell cds once gg is done.
+    hooks::run(Hook::PreCd, &plan, action)
+}
+
+/// Fetch reporoot. A configured SSH command is also recorded in the repo if
+/// it has none yet, e.g. because it was cloned before the config existed.
+fn fetch(reporoot: &Path, ssh_command: Option<&str>) -> Result<()> {
+    if let Some(ssh_command) = ssh_command {
+        let current = Command::new("git")
+            .arg("-C")
+            .arg(reporoot)
+            .args(["config", "--local", "core.sshCommand"])
+            .output()?;
+        if current.stdout.is_empty() {
+            let status = Command::new("git")
+                .arg("-C")
+                .arg(reporoot)
+                .args(["config", "--local", "core.sshCommand", ssh_command])
+                .status()?;
+            if !status.success() {
+                bail!("git config core.sshCommand failed ({status})");
+            }
+        }
+    }
+
+    let mut cmd = Command::new("git");
+    if let Some(ssh_command) = ssh_command {
+        cmd.env("GIT_SSH_COMMAND", ssh_command);
+    }
+    let status = cmd
+        .arg("-C")
+        .arg(reporoot)
+        .args([
+            "fetch",
+            "--all",
+            "--prune",
+            "--jobs=10",
+            "--recurse-submodules=yes",
+        ])
+        .stdin(Stdio::null())
+        .status();
+    match status {
+        Ok(s) if s.success() => Ok(()),
+        Ok(s) => bail!("git fetch failed ({s})"),
+        Err(e) => bail!("git not runnable: {e}"),
+    }
+}
+
+/// Clone url into reporoot without ever leaving a half-populated directory
+/// behind. The clone lands in a hidden staging dir in the nearest existing
+/// ancestor, and only once git succeeds are the host/org directories made
+/// and the clone renamed into place, so even a clone killed outright leaves
+/// no empty ones (which would make the host look known next time).
+fn clone(url: &str, reporoot: &Path, ssh_command: Option<&str>) -> Result<()> {
+    let orgroot = reporoot.parent().context("repo dir has no parent")?;
+
+    let staging = staging_dir(reporoot)?;
+    // Left behind by an interrupted clone, by this gg or an older one.
+    for stale in [&staging, &hidden_sibling(reporoot, ".gg-clone")?] {
+        if stale.exists() {
+            fs::remove_dir_all(stale)?;
+        }
+    }
+
+    let fallback = protocol::fallback()?;
+    let capture = fallback != Fallback::Off;
+    let mut outcome = git_clone(url, &staging, ssh_command, capture, true);
+
+    if let Ok(Cloned::Failed { ref stderr }) = outcome {
+        if let Some((host, proto, alt)) = alternate(url).filter(|_| is_access_error(stderr)) {
+            eprintln!("retrying over {proto}: {alt}");
+            if staging.exists() {
+                fs::remove_dir_all(&staging)?;
+            }
+            outcome = git_clone(&alt, &staging, ssh_command, capture, false);
+            if let Ok(Cloned::Ok) = outcome {
+                eprintln!("cloned over {proto}");
+                if fallback == Fallback::Remember {
+                    protocol::remember(&host, proto)?;
+                    eprintln!("gg will use {proto} for {host} from now on");
+                }
+            }
+        }
+    }
+
+    let result = match outcome {
+        Ok(Cloned::Ok) => create_dirs(orgroot).and_then(|created| {
+            let installed = install(&staging, reporoot);
+            if installed.is_err() {
+                for dir in created.iter().rev() {
+                    // remove_dir only succeeds on empty dirs, so anything
+                    // another process put there in the meantime survives.
+                    if fs::remove_dir(dir).is_err() {
+                        break;
+                    }
+                }
+            }
+            installed
+        }),
+        Ok(Cloned::Failed { .. }) => Err(anyhow::anyhow!("git clone failed")),
+        Err(e) => Err(e),
+    };
+
+    if result.is_err() && staging.exists() {
+        let _ = fs::remove_dir_all(&staging);
+    }
+    result
+}
+
+enum Cloned {
+    Ok,
+    Failed { stderr: String },
+}
+
+/// Run git clone. An SSH command is used for the clone (submodules included)
+/// and recorded as the new repo's core.sshCommand. When capturing, git's
+/// stderr is still echoed as it arrives (with progress forced on) but also
+/// kept, so the failure can be examined. Unless prompt is set, git fails
+/// rather than asking for a username and password: a retry over the other
+/// protocol may be for a repo that doesn't exist at all.
+fn git_clone(
+    url: &str,
+    dest: &Path,
+    ssh_command: Option<&str>,
+    capture: bool,
+    prompt: bool,
+) -> Result<Cloned> {
+    let mut cmd = Command::new("git");
+    cmd.arg("clone").arg("--recurse-submodules");
+    if !prompt {
+        cmd.env("GIT_TERMINAL_PROMPT", "0");
+    }
+    if let Some(ssh_command) = ssh_command {
+        cmd.env("GIT_SSH_COMMAND", ssh_command)
+            .arg("--config")
+            .arg(format!("core.sshCommand={ssh_command}"));
+    }
+    if capture {
+        cmd.arg("--progress").stderr(Stdio::piped());
+    }
+    cmd.arg("--").arg(url).arg(dest).stdin(Stdio::null());
+
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| anyhow::anyhow!("git not runnable: {e}"))?;
+    let mut stderr = Vec::new();
+    if let Some(mut pipe) = child.stderr.take() {
+        let mut out = io::stderr();
+        let mut buf = [0; 4096];
+        loop {
+            let n = pipe.read(&mut buf)?;
+            if n == 0 {
+                break;
+            }
+            out.write_all(&buf[..n])?;
+            stderr.extend_from_slice(&buf[..n]);
+        }
+    }
+
+    if child.wait()?.success() {
+        Ok(Cloned::Ok)
+    } else {
+        Ok(Cloned::Failed {
+            stderr: String::from_utf8_lossy(&stderr).into_owned(),
+        })
+    }
+}
+
+/// Move a finished clone into its final location, replacing an incomplete
+/// clone left there by an older gg.
+fn install(staging: &Path, reporoot: &Path) -> Result<()> {
+    if reporoot.exists() {
+        if !incomplete_clone(reporoot) {
+            bail!("{} appeared during clone", reporoot.display());
+        }
+        set_aside(reporoot)?;
+    }
+    fs::rename(staging, reporoot)
+        .with_context(|| format!("moving clone into {}", reporoot.display()))
+}
+
+/// Get an incomplete clone out of the way. A bare .git may still be
+/// someone's fresh git init, with config and hooks of their own, so it's
+/// moved to a hidden sibling (e.g. org/.repo.gg-incomplete) rather than
+/// deleted; an empty dir just goes.
+fn set_aside(reporoot: &Path) -> Result<()> {
+    if fs::remove_dir(reporoot).is_ok() {
+        return Ok(());
+    }
+    let mut aside = hidden_sibling(reporoot, ".gg-incomplete")?;
+    let mut n = 0;
+    while aside.exists() {
+        n += 1;
+        aside = hidden_sibling(reporoot, &format!(".gg-incomplete.{n}"))?;
+    }
+    fs::rename(reporoot, &aside).with_context(|| format!("moving {} aside", reporoot.display()))?;
+    eprintln!(
+        "moved incomplete clone {} aside to {}",
+        reporoot.display(),
+        aside.display()
+    );
+    Ok(())
+}
+
+/// The hidden dir a clone is staged in: in the nearest of reporoot's
+/// ancestors that exists, so it's on the same filesystem, and named for
+/// the repo (as its lock is) so concurrent clones don't collide.
+fn staging_dir(reporoot: &Path) -> Result<PathBuf> {
+    let base = reporoot
+        .ancestors()
+        .skip(1)
+        .find(|dir| dir.is_dir())
+        .context("repo dir has no existing ancestor")?;
+    Ok(base.join(format!(".gg-clone-{}", lock::id(reporoot))))
+}
+
+/// reporoot's name, hidden and with suffix, next to it.
+fn hidden_sibling(reporoot: &Path, suffix: &str) -> Result<PathBuf> {
+    let name = reporoot.file_name().context("repo dir has no name")?;
+    let mut sibling = OsString::from(".");
+    sibling.push(name);
+    sibling.push(suffix);
+    Ok(reporoot.with_file_name(sibling))
+}
+
+/// Create dir and any missing ancestors, returning the ones that were newly
+/// created, outermost first.
+fn create_dirs(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut missing = Vec::new();
+    let mut cursor = Some(dir);
+    while let Some(d) = cursor {
+        if d.is_dir() {
+            break;
+        }
+        missing.push(d.to_path_buf());
+        cursor = d.parent();
+    }
+    missing.reverse();
+    fs::create_dir_all(dir)?;
+    Ok(missing)
+}
+
+/// Whether reporoot holds the remains of a clone that never finished: an
+/// empty directory, or nothing but a .git that is broken or has no refs.
+/// Either way, no commits are lost by cloning in its place.
+pub fn incomplete_clone(reporoot: &Path) -> bool {
+    let entries: Vec<OsString> = match fs::read_dir(reporoot) {
+        Ok(entries) => entries
+            .filter_map(|e| e.ok())
+            .map(|e| e.file_name())
+            .collect(),
+        Err(_) => return false,
+    };
+    match entries.as_slice() {
+        [] => true,
+        [only] if only == ".git" => {
+            let refs = Command::new("git")
+                .arg("-C")
+                .arg(reporoot)
+                .args(["for-each-ref", "--count=1"])
+                .stderr(Stdio::null())
+                .output();
+            match refs {
+                Ok(out) => !out.status.success() || out.stdout.is_empty(),
+                Err(_) => false,
+            }
+        }
+        _ => false,
+    }
+}
@@ -1,13 +1,19 @@
+use std::collections::BTreeMap;
 use std::path::Path;
 
 use anyhow::Result;
 
+use crate::shell;
+
 pub fn bash(
     _command: Option<&str>,
     _prefix: Option<&str>,
-    _exepath: &Path,
-    _ggroot: &Path,
+    exepath: &Path,
+    _aliases: &BTreeMap<String, String>,
 ) -> Result<()> {
-    eprintln!("bash not supported yet");
+    // Only the prompt segment so far.
+    eprintln!("bash not supported yet, beyond gg_prompt");
+    let exepath = shell::escape(&exepath.display().to_string());
+    println!("gg_prompt() {{ '{exepath}' prompt \"$@\" 2>/dev/null; }}");
     Ok(())
 }
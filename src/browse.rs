@@ -0,0 +1,114 @@
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::LazyLock;
+
+use anyhow::{bail, Context, Result};
+use regex::Regex;
+
+use crate::config::Config;
+use crate::forge::{Lines, Web};
+use crate::here::{self, Location};
+
+static LINES_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(?P<path>.*):(?P<start>[0-9]+)(?:-(?P<end>[0-9]+))?$").unwrap());
+
+/// What page of a repo to show.
+pub enum Page {
+    /// A file or directory, with lines: path[:line[-line]]. None for the
+    /// repo's home page.
+    Path(Option<String>),
+    Branch,
+    Pulls,
+}
+
+/// Print the web page for the repo the current directory is in, and open it
+/// in a browser if asked.
+pub fn browse(config: &Config, page: Page, open: bool) -> Result<()> {
+    let (target, lines) = match page {
+        Page::Path(Some(ref target)) => split_lines(target)?,
+        _ => (PathBuf::from("."), None),
+    };
+    let Location { repo, tail } = here::locate(config, &target)?;
+    let Some(web) = Web::new(config, &repo.host, &repo.org, &repo.name)? else {
+        bail!("{}/{} has no web page", repo.host, repo.org);
+    };
+    let url = match page {
+        Page::Path(None) => web.home().to_string(),
+        Page::Path(Some(_)) if tail.is_empty() && lines.is_none() => web.home().to_string(),
+        Page::Path(Some(_)) => {
+            let commit = git(&repo.path, &["rev-parse", "HEAD"])
+                .with_context(|| format!("{} has no commits", repo.path.display()))?;
+            web.path(&commit, &tail, target.is_dir(), lines)
+        }
+        Page::Branch => {
+            let branch = git(&repo.path, &["symbolic-ref", "--short", "HEAD"])
+                .with_context(|| format!("{} is not on a branch", repo.path.display()))?;
+            web.branch(&branch)
+        }
+        Page::Pulls => web.pulls()?,
+    };
+
+    println!("{url}");
+    if open {
+        open_url(&url)?;
+    }
+    Ok(())
+}
+
+/// Split a :line or :start-end suffix off a path, if it has one and the
+/// whole thing isn't a path in its own right.
+fn split_lines(target: &str) -> Result<(PathBuf, Option<Lines>)> {
+    if Path::new(target).exists() {
+        return Ok((PathBuf::from(target), None));
+    }
+    let Some(m) = LINES_RE.captures(target) else {
+        bail!("{target} not found");
+    };
+    let path = PathBuf::from(&m["path"]);
+    if !path.is_file() {
+        bail!("{} not found", path.display());
+    }
+    let start = m["start"].parse()?;
+    let end = match m.name("end") {
+        Some(end) => Some(end.as_str().parse()?),
+        None => None,
+    };
+    if end.is_some_and(|end| end < start) {
+        bail!("invalid line range in {target}");
+    }
+    Ok((path, Some(Lines { start, end })))
+}
+
+fn git(dir: &Path, args: &[&str]) -> Option<String> {
+    let out = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .ok()?;
+    if !out.status.success() {
+        return None;
+    }
+    let out = String::from_utf8_lossy(&out.stdout).trim().to_string();
+    (!out.is_empty()).then_some(out)
+}
+
+/// Open url with $BROWSER (the first of its :-separated commands), else the
+/// system's opener.
+fn open_url(url: &str) -> Result<()> {
+    let browser = env::var("BROWSER").unwrap_or_default();
+    let opener = match browser.split(':').next() {
+        Some(browser) if !browser.is_empty() => browser,
+        _ if cfg!(target_os = "macos") => "open",
+        _ => "xdg-open",
+    };
+    let status = Command::new(opener)
+        .arg(url)
+        .status()
+        .with_context(|| format!("{opener} not runnable"))?;
+    if !status.success() {
+        bail!("{opener} failed ({status})");
+    }
+    Ok(())
+}
@@ -1,12 +1,13 @@
 use std::path::PathBuf;
 
-use clap::Parser;
+use clap::{CommandFactory, Parser, Subcommand};
 
 #[derive(Parser)]
 #[command(
     version,
     about = "gg makes it easy to find, fetch and work with your git repos.",
     long_about = None,
+    args_conflicts_with_subcommands = true,
 )]
 pub struct Cli {
     /// Repo URL or path (with --get), or alias command name (with -i)
@@ -21,6 +22,23 @@ pub struct Cli {
     #[arg(short = 'n', long)]
     pub dry_run: bool,
 
+    /// Editor to open the repo with this time (a known editor, a command
+    /// template, or - for none)
+    #[arg(short, long, value_name = "EDITOR")]
+    pub editor: Option<String>,
+
+    /// Land in a tmux session named after the repo instead of cd-ing
+    #[arg(long, group = "landing")]
+    pub tmux: bool,
+
+    /// Land in a zellij session named after the repo instead of cd-ing
+    #[arg(long, group = "landing")]
+    pub zellij: bool,
+
+    /// cd into the repo, even if the config says to land elsewhere
+    #[arg(long, group = "landing")]
+    pub cd: bool,
+
     /// Emit shell integration code; optionally define an alias command
     /// with a prefix (e.g., -i zsh ghg github.com)
     #[arg(short, long, value_enum, value_name = "SHELL")]
@@ -30,9 +48,132 @@ pub struct Cli {
     #[arg(long, hide = true)]
     pub get: bool,
 
+    /// (Internal) carry out a plan produced by --get
+    #[arg(long, hide = true, value_name = "PLAN")]
+    pub apply: Option<String>,
+
+    /// (Internal) switch to the tmux or zellij session in a plan
+    #[arg(long, hide = true, value_name = "PLAN")]
+    pub land: Option<String>,
+
     /// (Internal) repo spec prefix for alias invocations
     #[arg(long, hide = true)]
     pub prefix: Option<PathBuf>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Inspect or change gg's config file
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+
+    /// Set git identity per host/org via includeIf blocks in your gitconfig
+    Gitconfig {
+        /// Print the generated gitconfig instead of writing it
+        #[arg(long)]
+        print: bool,
+
+        /// Report cloned repos whose identity doesn't match the config
+        #[arg(long, conflicts_with = "print")]
+        check: bool,
+    },
+
+    /// Print the web page for the current repo, a file in it, or lines
+    Browse {
+        /// File or directory in the repo, optionally with :line or
+        /// :start-end
+        #[arg(value_name = "PATH[:LINE]")]
+        target: Option<String>,
+
+        /// The current branch's page
+        #[arg(long, conflicts_with_all = ["target", "pulls"])]
+        branch: bool,
+
+        /// The list of pull requests
+        #[arg(long, conflicts_with = "target")]
+        pulls: bool,
+
+        /// Also open the page, with $BROWSER or the system's opener
+        #[arg(short, long)]
+        open: bool,
+    },
+
+    /// Print where a repo lives (or would once cloned), touching nothing
+    Path {
+        /// Repo URL or path, as for gg itself
+        spec: String,
+
+        /// Fail if the repo isn't cloned
+        #[arg(long)]
+        exists: bool,
+    },
+
+    /// Print the current repo for a shell prompt, quickly
+    Prompt {
+        /// Abbreviate with the longest matching alias (e.g., ghg:org/repo)
+        #[arg(short, long)]
+        short: bool,
+
+        /// Also identify repos outside the roots by origin (runs git)
+        #[arg(long)]
+        origin: bool,
+    },
+
+    /// Print completions for a partial repo spec, for shell completion
+    Complete {
+        /// The spec so far
+        #[arg(default_value = "")]
+        word: String,
+
+        /// Complete under an alias's prefix (e.g., github.com/org)
+        #[arg(long)]
+        prefix: Option<String>,
+    },
+
+    /// Show which repo a directory (default the current one) is in
+    Here {
+        dir: Option<PathBuf>,
+
+        /// Print JSON instead of key=value lines
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Move cloned repos to where the configured roots and layout put them
+    Migrate {
+        /// Show what would move without moving anything
+        #[arg(short = 'n', long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ConfigAction {
+    /// Print a setting (e.g., root, or hosts.github.com.protocol)
+    Get { key: String },
+
+    /// Change a setting
+    Set { key: String, value: String },
+
+    /// Print every setting in the config file
+    List,
+
+    /// Open the config file in $VISUAL or $EDITOR
+    Edit,
+}
+
+/// The names of gg's subcommands, which the shell integration passes straight
+/// through to gg rather than treating as repo specs.
+pub fn subcommands() -> Vec<String> {
+    Cli::command()
+        .get_subcommands()
+        .map(|c| c.get_name().to_string())
+        .collect()
 }
 
 #[derive(clap::ValueEnum, Clone)]
@@ -40,4 +181,6 @@ pub enum Shell {
     Zsh,
     Bash,
     Fish,
+    /// A custom module for starship.toml
+    Starship,
 }
@@ -0,0 +1,205 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+use std::time::SystemTime;
+
+use anyhow::Result;
+use regex::Regex;
+
+use crate::config::Config;
+use crate::getgit::LOCAL_HOST;
+use crate::pattern;
+use crate::repos;
+use crate::verify;
+
+/// The URL forms gg takes, up to where the host starts.
+static LEAD_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(?:git@|(?:https?|git)://)").unwrap());
+
+/// What gg knows of: repos cloned or recently verified, and hosts and orgs
+/// with settings of their own.
+struct Known {
+    /// Cloned repos with where they are; verified ones with None.
+    repos: BTreeMap<(String, String, String), Option<PathBuf>>,
+    hosts: BTreeSet<String>,
+    orgs: BTreeSet<(String, String)>,
+}
+
+/// Print what a partial repo spec (word) could go on to be, one per line
+/// as value, a tab, then a description. Hosts and orgs complete with their
+/// trailing separator, so the shell knows to keep going. With prefix (an
+/// alias's), word is taken to be under it, and so are the values.
+pub fn complete(config: &Config, prefix: Option<&str>, word: &str) -> Result<()> {
+    let known = known(config)?;
+    let candidates = match prefix {
+        Some(prefix) => {
+            let prefix = format!("{}/", prefix.trim_end_matches('/'));
+            candidates(&known, "", &format!("{prefix}{word}"))
+                .into_iter()
+                .filter_map(|(value, description)| {
+                    Some((value.strip_prefix(&prefix)?.to_string(), description))
+                })
+                .collect()
+        }
+        None => {
+            let lead = LEAD_RE.find(word).map_or("", |m| m.as_str());
+            candidates(&known, lead, &word[lead.len()..])
+        }
+    };
+    for (value, description) in candidates {
+        println!("{value}\t{description}");
+    }
+    Ok(())
+}
+
+fn known(config: &Config) -> Result<Known> {
+    let mut known = Known {
+        repos: BTreeMap::new(),
+        hosts: BTreeSet::new(),
+        orgs: BTreeSet::new(),
+    };
+    for repo in repos::list(config)? {
+        known
+            .repos
+            .insert((repo.host, repo.org, repo.name), Some(repo.path));
+    }
+    for repo in verify::cached() {
+        known.repos.entry(repo).or_insert(None);
+    }
+    for (host, org, _) in known.repos.keys() {
+        known.hosts.insert(host.clone());
+        known.orgs.insert((host.clone(), org.clone()));
+    }
+    // Sections name hosts and orgs worth offering before anything's cloned
+    // from them, unless they're patterns.
+    for host in config.hosts.keys() {
+        if !pattern::has_wildcards(host) {
+            known.hosts.insert(host.clone());
+        }
+    }
+    for key in config.orgs.keys() {
+        if let Some((host, org)) = key.split_once('/') {
+            if !pattern::has_wildcards(key) {
+                known.hosts.insert(host.to_string());
+                known.orgs.insert((host.to_string(), org.to_string()));
+            }
+        }
+    }
+    Ok(known)
+}
+
+/// Completions for rest, the part of a spec after lead (a URL scheme or
+/// git@, if any), each with lead put back.
+fn candidates(known: &Known, lead: &str, rest: &str) -> Vec<(String, String)> {
+    // git@host:org/repo is the only form whose host ends in a colon.
+    let separator = if lead == "git@" { ':' } else { '/' };
+    let Some(end) = rest.find(['/', ':']) else {
+        return known
+            .hosts
+            .iter()
+            .filter(|host| host.starts_with(rest))
+            // Local repos have no URL.
+            .filter(|host| lead.is_empty() || host.as_str() != LOCAL_HOST)
+            .map(|host| {
+                let description = count(known, |(h, _, _)| h == host);
+                (format!("{lead}{host}{separator}"), description)
+            })
+            .collect();
+    };
+    let (host, separator, after) = (&rest[..end], &rest[end..end + 1], &rest[end + 1..]);
+    let Some((org, partial)) = after.split_once('/') else {
+        return known
+            .orgs
+            .iter()
+            .filter(|(h, org)| h == host && org.starts_with(after))
+            .map(|(_, org)| {
+                let description = count(known, |(h, o, _)| h == host && o == org);
+                (format!("{lead}{host}{separator}{org}/"), description)
+            })
+            .collect();
+    };
+    known
+        .repos
+        .iter()
+        .filter(|((h, o, name), _)| h == host && o == org && name.starts_with(partial))
+        .map(|((_, _, name), path)| {
+            let description = match path {
+                Some(path) => describe(path),
+                None => "not cloned".to_string(),
+            };
+            (format!("{lead}{host}{separator}{org}/{name}"), description)
+        })
+        .collect()
+}
+
+/// How many of the known repos are in a host or org, and how many of
+/// those aren't cloned.
+fn count(known: &Known, within: impl Fn(&(String, String, String)) -> bool) -> String {
+    let (cloned, other): (Vec<_>, Vec<_>) = known
+        .repos
+        .iter()
+        .filter(|(key, _)| within(key))
+        .partition(|(_, path)| path.is_some());
+    let total = cloned.len() + other.len();
+    let repos = match total {
+        0 => return "no repos yet".to_string(),
+        1 => "1 repo".to_string(),
+        n => format!("{n} repos"),
+    };
+    match other.len() {
+        0 => repos,
+        n => format!("{repos}, {n} not cloned"),
+    }
+}
+
+/// A cloned repo's branch and when it was last fetched, read straight from
+/// its git dir, as running git for every repo would be too slow to tab.
+fn describe(path: &Path) -> String {
+    let git_dir = git_dir(path);
+    let branch = fs::read_to_string(git_dir.join("HEAD"))
+        .ok()
+        .map(|head| {
+            let head = head.trim();
+            match head.strip_prefix("ref: refs/heads/") {
+                Some(branch) => branch.to_string(),
+                None => format!("detached at {}", head.chars().take(7).collect::<String>()),
+            }
+        })
+        .unwrap_or_else(|| "no branch".to_string());
+    let fetched = fs::metadata(git_dir.join("FETCH_HEAD"))
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|time| SystemTime::now().duration_since(time).ok())
+        .map(|age| format!("fetched {}", ago(age.as_secs())))
+        .unwrap_or_else(|| "never fetched".to_string());
+    format!("{branch}, {fetched}")
+}
+
+/// The repo's git dir: .git, or where a .git file (as in a worktree)
+/// points.
+fn git_dir(path: &Path) -> PathBuf {
+    let dot_git = path.join(".git");
+    match fs::read_to_string(&dot_git) {
+        Ok(content) => match content.trim().strip_prefix("gitdir: ") {
+            Some(dir) => path.join(dir),
+            None => dot_git,
+        },
+        Err(_) => dot_git,
+    }
+}
+
+/// secs as a rough age, e.g., 3d ago.
+fn ago(secs: u64) -> String {
+    const MINUTE: u64 = 60;
+    const HOUR: u64 = 60 * MINUTE;
+    const DAY: u64 = 24 * HOUR;
+    match secs {
+        s if s < MINUTE => "just now".to_string(),
+        s if s < HOUR => format!("{}m ago", s / MINUTE),
+        s if s < DAY => format!("{}h ago", s / HOUR),
+        s if s < 60 * DAY => format!("{}d ago", s / DAY),
+        s if s < 365 * DAY => format!("{}mo ago", s / (30 * DAY)),
+        s => format!("{}y ago", s / (365 * DAY)),
+    }
+}
@@ -0,0 +1,242 @@
+use std::fs;
+use std::io::{self, Write};
+use std::process::Command;
+
+use anyhow::{bail, Context, Result};
+use toml_edit::{DocumentMut, Item, Table};
+
+use crate::cli::ConfigAction;
+use crate::config::{self, Config};
+use crate::env;
+
+/// Environment variables that override top-level settings.
+const OVERRIDES: &[(&str, &str)] = &[
+    ("root", "GGROOT"),
+    ("protocol", "GGHTTP"),
+    ("viewer", "GGDIRVIEWER"),
+    ("auto_cd", "GGNOAUTOCD"),
+    ("verify", "GGVERIFY"),
+];
+
+pub fn configure(action: ConfigAction) -> Result<()> {
+    match action {
+        ConfigAction::Get { key } => get(&key),
+        ConfigAction::Set { key, value } => set(&key, &value),
+        ConfigAction::List => list(),
+        ConfigAction::Edit => edit(),
+    }
+}
+
+fn get(key: &str) -> Result<()> {
+    let path = parse_key(key)?;
+    let table = read_table()?;
+    let mut value = None;
+    let mut cursor = Some(&table);
+    for (i, segment) in path.iter().enumerate() {
+        let Some(item) = cursor.and_then(|t| t.get(segment)) else {
+            break;
+        };
+        if i == path.len() - 1 {
+            value = Some(item);
+        }
+        cursor = item.as_table();
+    }
+    note_override(&path);
+    match value {
+        Some(toml::Value::String(s)) => println!("{s}"),
+        Some(value) => println!("{value}"),
+        None => bail!("{key} is not set"),
+    }
+    Ok(())
+}
+
+fn set(key: &str, value: &str) -> Result<()> {
+    let path = parse_key(key)?;
+    let file = config::path()?;
+    let mut doc: DocumentMut = match fs::read_to_string(&file) {
+        Ok(content) => content
+            .parse()
+            .with_context(|| format!("reading {}", file.display()))?,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => DocumentMut::new(),
+        Err(e) => return Err(e).with_context(|| format!("reading {}", file.display())),
+    };
+
+    let (field, tables) = path.split_last().context("empty key")?;
+    let mut table: &mut Table = doc.as_table_mut();
+    for (depth, name) in tables.iter().enumerate() {
+        let item = table.entry(name).or_insert_with(|| {
+            let mut t = Table::new();
+            // [hosts] with nothing but subtables needs no header of its own.
+            t.set_implicit(depth + 1 < tables.len());
+            Item::Table(t)
+        });
+        table = item
+            .as_table_mut()
+            .with_context(|| format!("{name} is not a table"))?;
+    }
+    table[field.as_str()] = match field.as_str() {
+        "port" => toml_edit::value(
+            value
+                .parse::<i64>()
+                .with_context(|| format!("{key} must be a number"))?,
+        ),
+        "auto_cd" | "ignore_case" | "keep_credentials" | "verify" | "confirm_new_hosts"
+        | "strict_hooks" => toml_edit::value(
+            value
+                .parse::<bool>()
+                .with_context(|| format!("{key} must be true or false"))?,
+        ),
+        "allow" | "deny" => list_value(value).with_context(|| format!("cannot set {key}"))?,
+        _ => toml_edit::value(value),
+    };
+
+    // Refuse to write anything gg couldn't read back.
+    let content = doc.to_string();
+    toml::from_str::<Config>(&content)
+        .with_context(|| format!("cannot set {key}"))?
+        .check()?;
+
+    if let Some(dir) = file.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(&file, content)?;
+    note_override(&path);
+    Ok(())
+}
+
+/// A list setting, given as a TOML array (["a", "b"]) or comma-separated
+/// (a,b).
+fn list_value(value: &str) -> Result<Item> {
+    let array = match value.trim_start().starts_with('[') {
+        true => match value.parse::<toml_edit::Value>()? {
+            toml_edit::Value::Array(array) => array,
+            _ => bail!("expected an array, not {value}"),
+        },
+        false => value
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .collect(),
+    };
+    Ok(toml_edit::value(array))
+}
+
+fn list() -> Result<()> {
+    let table = read_table()?;
+    let mut out = io::stdout();
+    let mut entries = Vec::new();
+    flatten(&table, &mut Vec::new(), &mut entries);
+    for (key, value) in entries {
+        writeln!(out, "{key} = {value}")?;
+    }
+    Ok(())
+}
+
+fn edit() -> Result<()> {
+    let file = config::path()?;
+    if let Some(dir) = file.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let editor = match env::var("VISUAL").as_str() {
+        "" => match env::var("EDITOR").as_str() {
+            "" => "vi".to_string(),
+            editor => editor.to_string(),
+        },
+        editor => editor.to_string(),
+    };
+    // The editor may come with arguments, so let the shell split it.
+    let status = Command::new("sh")
+        .arg("-c")
+        .arg(format!("{editor} \"$1\""))
+        .arg("sh")
+        .arg(&file)
+        .status()
+        .with_context(|| format!("running {editor}"))?;
+    if !status.success() {
+        bail!("{editor} failed ({status})");
+    }
+    Config::load()?.check()
+}
+
+/// Split a config key into its table path. Host and org names contain dots,
+/// so hosts.github.com.protocol means protocol in [hosts."github.com"].
+/// TOML-style quoting (hosts."github.com".protocol) works too.
+fn parse_key(key: &str) -> Result<Vec<String>> {
+    if key.contains('"') {
+        return parse_quoted_key(key);
+    }
+    Ok(match key.split_once('.') {
+        None => vec![key.to_string()],
+        Some((table @ ("hosts" | "orgs"), rest)) => match rest.rsplit_once('.') {
+            Some((name, field)) => vec![table.to_string(), name.to_string(), field.to_string()],
+            None => bail!("expected {table}.NAME.SETTING, not {key}"),
+        },
+        Some(("aliases", name)) => vec!["aliases".to_string(), name.to_string()],
+        Some(_) => bail!("unknown config key {key}"),
+    })
+}
+
+fn parse_quoted_key(key: &str) -> Result<Vec<String>> {
+    let mut segments = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    for c in key.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            '.' if !quoted => segments.push(std::mem::take(&mut current)),
+            c => current.push(c),
+        }
+    }
+    if quoted {
+        bail!("unterminated quote in {key}");
+    }
+    segments.push(current);
+    Ok(segments)
+}
+
+/// Collect every leaf of table as a (dotted key, TOML value) pair.
+fn flatten(table: &toml::Table, prefix: &mut Vec<String>, out: &mut Vec<(String, String)>) {
+    for (name, value) in table {
+        prefix.push(quote_key(name));
+        match value {
+            toml::Value::Table(t) => flatten(t, prefix, out),
+            value => out.push((prefix.join("."), value.to_string())),
+        }
+        prefix.pop();
+    }
+}
+
+fn quote_key(name: &str) -> String {
+    if !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        name.to_string()
+    } else {
+        format!("\"{name}\"")
+    }
+}
+
+fn read_table() -> Result<toml::Table> {
+    let file = config::path()?;
+    match fs::read_to_string(&file) {
+        Ok(content) => {
+            toml::from_str(&content).with_context(|| format!("reading {}", file.display()))
+        }
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(toml::Table::new()),
+        Err(e) => Err(e).with_context(|| format!("reading {}", file.display())),
+    }
+}
+
+/// Warn when an environment variable overrides a setting.
+fn note_override(path: &[String]) {
+    if let [key] = path {
+        if let Some((_, var)) = OVERRIDES.iter().find(|(k, _)| k == key) {
+            let value = env::var(var);
+            if !value.is_empty() {
+                eprintln!("note: {var}={value} overrides {key}");
+            }
+        }
+    }
+}
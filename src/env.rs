@@ -26,3 +26,31 @@ pub fn squiggler<'a>(home: &'a Path) -> Box<dyn Fn(&Path) -> PathBuf + 'a> {
 pub fn var(key: &str) -> String {
     std::env::var(key).unwrap_or_default()
 }
+
+/// Where gg keeps disposable state such as lock files ($XDG_CACHE_HOME/gg).
+pub fn cache_dir() -> Result<PathBuf> {
+    let base = match var("XDG_CACHE_HOME").as_str() {
+        "" => home()?.join(".cache"),
+        dir => PathBuf::from(dir),
+    };
+    Ok(base.join("gg"))
+}
+
+/// Where gg keeps things it has learned, such as which protocol works for a
+/// host ($XDG_STATE_HOME/gg).
+pub fn state_dir() -> Result<PathBuf> {
+    let base = match var("XDG_STATE_HOME").as_str() {
+        "" => home()?.join(".local/state"),
+        dir => PathBuf::from(dir),
+    };
+    Ok(base.join("gg"))
+}
+
+/// Where gg's config lives ($XDG_CONFIG_HOME/gg).
+pub fn config_dir() -> Result<PathBuf> {
+    let base = match var("XDG_CONFIG_HOME").as_str() {
+        "" => home()?.join(".config"),
+        dir => PathBuf::from(dir),
+    };
+    Ok(base.join("gg"))
+}
@@ -1,13 +1,19 @@
+use std::collections::BTreeMap;
 use std::path::Path;
 
 use anyhow::Result;
 
+use crate::shell;
+
 pub fn fish(
     _command: Option<&str>,
     _prefix: Option<&str>,
-    _exepath: &Path,
-    _ggroot: &Path,
+    exepath: &Path,
+    _aliases: &BTreeMap<String, String>,
 ) -> Result<()> {
-    eprintln!("fish not supported yet");
+    // Only the prompt segment so far.
+    eprintln!("fish not supported yet, beyond gg_prompt");
+    let exepath = shell::escape(&exepath.display().to_string());
+    println!("function gg_prompt; '{exepath}' prompt $argv 2>/dev/null; end");
     Ok(())
 }
@@ -0,0 +1,128 @@
+use anyhow::{bail, Result};
+use serde::Deserialize;
+
+use crate::config::Config;
+use crate::getgit::LOCAL_HOST;
+use crate::template;
+
+/// A kind of forge, which decides how its web pages are laid out.
+#[derive(Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Forge {
+    Github,
+    Gitlab,
+    /// Gitea, and Forgejo, which shares its URLs.
+    #[serde(alias = "forgejo")]
+    Gitea,
+    Bitbucket,
+    Sourcehut,
+}
+
+impl Forge {
+    /// The forge a host runs, going by its name. Hosts that don't say are
+    /// taken to look like GitHub, as most forges' home pages do.
+    fn guess(host: &str) -> Forge {
+        let has = |name: &str| host.split(['.', '-']).any(|part| part == name);
+        if has("gitlab") {
+            Forge::Gitlab
+        } else if has("gitea") || has("forgejo") || host == "codeberg.org" {
+            Forge::Gitea
+        } else if has("bitbucket") {
+            Forge::Bitbucket
+        } else if host.ends_with("sr.ht") {
+            Forge::Sourcehut
+        } else {
+            Forge::Github
+        }
+    }
+}
+
+/// Lines of a file to point at: a line, or an inclusive range.
+#[derive(Clone, Copy)]
+pub struct Lines {
+    pub start: u32,
+    pub end: Option<u32>,
+}
+
+/// A repo's pages on its forge.
+pub struct Web {
+    forge: Forge,
+    /// The repo's home page, which the other pages hang off.
+    home: String,
+}
+
+impl Web {
+    /// The web pages of a repo, if it has any: local repos don't. A host
+    /// or org's web_url template and forge setting override what gg makes
+    /// of the host name.
+    pub fn new(config: &Config, host: &str, org: &str, repo: &str) -> Result<Option<Web>> {
+        if host == LOCAL_HOST {
+            return Ok(None);
+        }
+        let settings = config.settings(host, org);
+        let home = match settings.web_url {
+            Some(ref url) => {
+                template::render(url, &[("host", host), ("org", org), ("repo", repo)])?
+            }
+            None => format!("https://{host}/{org}/{repo}"),
+        };
+        Ok(Some(Web {
+            forge: settings.forge.unwrap_or_else(|| Forge::guess(host)),
+            home: home.trim_end_matches('/').to_string(),
+        }))
+    }
+
+    pub fn home(&self) -> &str {
+        &self.home
+    }
+
+    /// The page for a branch.
+    pub fn branch(&self, branch: &str) -> String {
+        let home = &self.home;
+        match self.forge {
+            Forge::Github | Forge::Sourcehut => format!("{home}/tree/{branch}"),
+            Forge::Gitlab => format!("{home}/-/tree/{branch}"),
+            Forge::Gitea => format!("{home}/src/branch/{branch}"),
+            Forge::Bitbucket => format!("{home}/src/{branch}"),
+        }
+    }
+
+    /// The page for a file or directory (path, relative to the repo's top)
+    /// as of commit, optionally pointing at some of its lines.
+    pub fn path(&self, commit: &str, path: &str, dir: bool, lines: Option<Lines>) -> String {
+        let home = &self.home;
+        let page = match (self.forge, dir) {
+            (Forge::Github, false) => format!("{home}/blob/{commit}/{path}"),
+            (Forge::Github, true) => format!("{home}/tree/{commit}/{path}"),
+            (Forge::Gitlab, false) => format!("{home}/-/blob/{commit}/{path}"),
+            (Forge::Gitlab, true) => format!("{home}/-/tree/{commit}/{path}"),
+            (Forge::Gitea, _) => format!("{home}/src/commit/{commit}/{path}"),
+            (Forge::Bitbucket, _) => format!("{home}/src/{commit}/{path}"),
+            (Forge::Sourcehut, _) => format!("{home}/tree/{commit}/item/{path}"),
+        };
+        let Some(Lines { start, end }) = lines else {
+            return page;
+        };
+        let anchor = match (self.forge, end) {
+            (Forge::Bitbucket, None) => format!("lines-{start}"),
+            (Forge::Bitbucket, Some(end)) => format!("lines-{start}:{end}"),
+            (_, None) => format!("L{start}"),
+            (Forge::Github | Forge::Gitea, Some(end)) => format!("L{start}-L{end}"),
+            (Forge::Gitlab | Forge::Sourcehut, Some(end)) => format!("L{start}-{end}"),
+        };
+        format!("{page}#{anchor}")
+    }
+
+    /// The list of open pull (or merge) requests.
+    pub fn pulls(&self) -> Result<String> {
+        let home = &self.home;
+        Ok(match self.forge {
+            Forge::Github | Forge::Gitea => format!("{home}/pulls"),
+            Forge::Gitlab => format!("{home}/-/merge_requests"),
+            Forge::Bitbucket => format!("{home}/pull-requests"),
+            Forge::Sourcehut => {
+                bail!("sourcehut has no pull requests; patches go to mailing lists")
+            }
+        })
+    }
+}
@@ -1,13 +1,27 @@
-use std::fs::create_dir_all;
-use std::io::{self, Write};
-use std::path::Path;
+use std::collections::BTreeSet;
+use std::io::{self, IsTerminal, Write};
+use std::path::{Component, Path, PathBuf};
 use std::process::Command;
 use std::sync::LazyLock;
 
-use anyhow::{bail, Result};
-use regex::Regex;
+use anyhow::{bail, Context, Result};
+use regex::{Captures, Regex};
 
+use crate::apply::incomplete_clone;
+use crate::config::Config;
 use crate::env;
+use crate::land::Landing;
+use crate::layout;
+use crate::plan::{Action, Plan};
+use crate::policy;
+use crate::protocol::{self, alternate, Fallback};
+use crate::repos;
+use crate::rewrite::Rewrites;
+use crate::spec;
+use crate::sshconfig;
+use crate::suggest;
+use crate::verify;
+use crate::viewer::Viewer;
 
 static URL_RE: LazyLock<Regex> = LazyLock::new(|| {
     Regex::new(
@@ -15,7 +29,8 @@ static URL_RE: LazyLock<Regex> = LazyLock::new(|| {
         ^
         (?P<prefix>
             git@(?P<git_host>[^:]+):
-          | https?://(?P<http_host>[^/]+)/
+          | (?: https? | git )://(?P<url_host>[^/]+)/
+          | (?P<local> (?: file:// )? / (?: [^/]+ / )* )
           | (?P<host>[^/:]+) [/:] )
         (?P<org>[^/]+)
         /
@@ -26,110 +41,585 @@ static URL_RE: LazyLock<Regex> = LazyLock::new(|| {
     .unwrap()
 });
 
-pub fn getgit(path: &Path, prefix: Option<&Path>, dry_run: bool, ggroot: &Path) -> Result<()> {
+/// The pseudo-host that local repos (file:// URLs and absolute paths) are
+/// kept under.
+pub const LOCAL_HOST: &str = "local";
+
+/// The host a spec names, as typed.
+fn host_of<'a>(m: &Captures<'a>) -> Option<&'a str> {
+    if m.name("local").is_some() {
+        return Some(LOCAL_HOST);
+    }
+    m.name("git_host")
+        .or(m.name("url_host"))
+        .or(m.name("host"))
+        .map(|host| host.as_str())
+}
+
+/// Whether a spec's host may be an SSH alias.
+fn over_ssh(m: &Captures) -> bool {
+    m.name("git_host").is_some() || m.name("host").is_some()
+}
+
+/// A spec, parsed and placed: where its repo lives, or would once cloned.
+/// Working this out touches nothing on disk or the network.
+pub struct Target {
+    /// The spec, with noise stripped and git's URL rewriting undone.
+    url: String,
+    kind: Kind,
+    /// Host, org and repo as the spec gives them.
+    host: String,
+    org: String,
+    repo: String,
+    /// user[:password] from the spec's URL.
+    userinfo: Option<String>,
+    /// Line to open, from a #L10-style anchor.
+    pub line: Option<u32>,
+    /// The repo's real host (past any SSH alias, lowercased), and its org
+    /// and repo in the case of any existing clone.
+    pub dirhost: String,
+    pub disk_org: String,
+    pub disk_repo: String,
+    /// Path within the repo the spec points at, with a leading /, or "".
+    pub tail: String,
+    pub root: PathBuf,
+    /// The layout that places the repo under root.
+    layout: String,
+    /// The first directory under root on the way to the repo, usually the
+    /// host dir.
+    pub top_dir: PathBuf,
+    pub repo_dir: PathBuf,
+    pub cd_dir: String,
+}
+
+/// How a spec names its repo, which decides the URL to clone it from.
+enum Kind {
+    /// git@host:org/repo
+    Ssh,
+    /// An https://, http:// or git:// URL, given up to the org.
+    Url(String),
+    /// A file:// URL or absolute path.
+    Local,
+    /// host/org/repo
+    Shorthand,
+}
+
+/// Parse spec and work out where its repo goes.
+pub fn resolve(spec: &str, config: &Config, rewrites: &Rewrites) -> Result<Target> {
+    let home = env::home()?;
+    let squiggle = env::squiggler(home.as_path());
+    if spec.chars().any(char::is_control) {
+        bail!(
+            "invalid spec {:?}: contains control characters",
+            spec::redact(spec)
+        );
+    }
+
+    // Query strings and fragments (?tab=readme, #readme) don't change which
+    // repo a link is for; only a #L10 line anchor is worth keeping.
+    let cleaned = spec::clean(spec);
+    let url = cleaned.spec;
+
+    // A pasted URL may already have been rewritten by url.<base>.insteadOf
+    // (e.g., a mirror of github.com). The repo's place on disk comes from the
+    // canonical URL it was rewritten from.
+    let url = match rewrites
+        .unrewrite(&url)
+        .into_iter()
+        .find(|canonical| URL_RE.is_match(canonical))
+    {
+        Some(canonical) => {
+            eprintln!("{url} is rewritten from {canonical}");
+            canonical
+        }
+        None => url,
+    };
+
+    // A local path may run on into the repo (e.g., a file in it), and only
+    // the disk can tell where the repo stops.
+    let (url, local_tail) = split_local(&url);
+
+    let Some(m) = URL_RE.captures(url.as_str()) else {
+        bail!("invalid path: {}", spec::redact(spec));
+    };
+    let Some(host) = host_of(&m) else {
+        bail!("no host in URL");
+    };
+
+    let org = m.name("org").unwrap().as_str();
+    let repo = m.name("repo").unwrap().as_str();
+    let tail = match m.name("tail") {
+        Some(cap) => cap.as_str().to_string(),
+        None => local_tail,
+    };
+
+    // Specs come from pasted links too, so nothing in them may steer gg (or
+    // git, or ssh) outside the repo tree.
+    check_name("host", host)?;
+    check_name("org", org)?;
+    check_name("repo", repo)?;
+    check_tail(&tail)?;
+
+    // An SSH host alias (e.g., gh-work for github.com with a different
+    // identity) is cloned through, but lives under its real host.
+    let dirhost = match over_ssh(&m) {
+        true => sshconfig::resolve_alias(host),
+        false => None,
+    };
+    if let Some(ref real) = dirhost {
+        eprintln!("{host} is an SSH alias for {real}");
+    }
+    // Host names are case-insensitive, so the host dir is lowercase.
+    let dirhost = dirhost.as_deref().unwrap_or(host).to_lowercase();
+    check_name("host", &dirhost)?;
+
+    let kind = if m.name("git_host").is_some() {
+        Kind::Ssh
+    } else if m.name("local").is_some() {
+        Kind::Local
+    } else if m.name("url_host").is_some() {
+        Kind::Url(m.name("prefix").unwrap().as_str().to_string())
+    } else {
+        Kind::Shorthand
+    };
+
+    let root = config.root_for(&dirhost, org)?;
+    let layout = config.layout(&dirhost, org);
+    let mut relpath = layout::render(&layout, &dirhost, org, repo)?;
+
+    // Where the host ignores case, reuse a clone whose name differs only in
+    // case instead of cloning the repo a second time.
+    let mut names = (org.to_string(), repo.to_string());
+    if config.ignore_case(&dirhost, org) {
+        let existing = layout::match_case(&root, &relpath);
+        if existing != relpath {
+            eprintln!(
+                "warning: {} differs in case from existing {}, using that",
+                squiggle(root.join(&relpath).as_path()).display(),
+                squiggle(root.join(&existing).as_path()).display()
+            );
+            if let Some(parsed) = layout::parse(&layout, &existing) {
+                names = (
+                    parsed.org.unwrap_or(names.0),
+                    parsed.repo.unwrap_or(names.1),
+                );
+            }
+            relpath = existing;
+        }
+    }
+
+    let top_dir = match relpath.components().next() {
+        Some(top) => root.join(top),
+        None => bail!("empty layout"),
+    };
+    let repo_dir = root.join(&relpath);
+    let cd_dir = format!("{}{}", repo_dir.display(), tail);
+    // Belt and braces: the checks above should make this impossible.
+    if repo_dir == root
+        || !repo_dir.starts_with(&root)
+        || !Path::new(&cd_dir).starts_with(&repo_dir)
+    {
+        bail!("{cd_dir} is outside {}", root.display());
+    }
+
+    Ok(Target {
+        kind,
+        host: host.to_string(),
+        org: org.to_string(),
+        repo: repo.to_string(),
+        userinfo: cleaned.userinfo,
+        line: cleaned.line,
+        dirhost,
+        disk_org: names.0,
+        disk_repo: names.1,
+        tail,
+        root,
+        layout,
+        top_dir,
+        repo_dir,
+        cd_dir,
+        url,
+    })
+}
+
+/// Split a local spec (a path or file:// URL) into the repo and the path
+/// within it, going by the nearest enclosing dir that holds a repo (a .git,
+/// or a bare repo's HEAD and objects). A path in no repo is taken whole,
+/// for the clone to report as missing. Other specs pass through untouched.
+fn split_local(url: &str) -> (String, String) {
+    let (scheme, path) = match url.strip_prefix("file://") {
+        Some(path) => ("file://", path),
+        None => ("", url),
+    };
+    if !path.starts_with('/') {
+        return (url.to_string(), String::new());
+    }
+    let is_repo = |dir: &Path| {
+        dir.join(".git").exists() || (dir.join("HEAD").is_file() && dir.join("objects").is_dir())
+    };
+    let path = Path::new(path);
+    match path.ancestors().find(|dir| is_repo(dir)) {
+        Some(repo) if repo != path => {
+            let tail = path.strip_prefix(repo).unwrap_or(path);
+            (
+                format!("{scheme}{}", repo.display()),
+                format!("/{}", tail.display()),
+            )
+        }
+        _ => (url.to_string(), String::new()),
+    }
+}
+
+/// Print where spec's repo lives, or would once cloned, touching nothing.
+/// With must_exist, a repo that isn't cloned yet is an error.
+pub fn path(spec: &str, config: &Config, must_exist: bool) -> Result<()> {
+    let target = resolve(spec, config, &Rewrites::load())?;
+    let cloned = target.repo_dir.is_dir() && !incomplete_clone(&target.repo_dir);
+    if !cloned && must_exist {
+        bail!("{} is not cloned", target.repo_dir.display());
+    }
+    println!("{}", target.cd_dir);
+    if !cloned && io::stderr().is_terminal() {
+        eprintln!("(not cloned yet)");
+    }
+    Ok(())
+}
+
+pub fn getgit(
+    path: &Path,
+    prefix: Option<&Path>,
+    editor: Option<&str>,
+    landing: Option<Landing>,
+    dry_run: bool,
+    config: &Config,
+) -> Result<()> {
     let home = env::home()?;
     let squiggle = env::squiggler(home.as_path());
-    let url = match prefix {
+    let spec = match prefix {
         Some(prefix) => prefix.join(path).to_path_buf(),
         None => path.to_path_buf(),
     }
     .display()
     .to_string();
+    let rewrites = Rewrites::load();
+    let target = resolve(&spec, config, &rewrites)?;
+    let host = target.host.as_str();
+    let dirhost = target.dirhost.as_str();
 
-    let m = URL_RE.captures(url.as_str());
-
-    match m {
-        Some(m) => {
-            let host = match m.name("git_host") {
-                Some(host) => host,
-                None => match m.name("http_host") {
-                    Some(host) => host,
-                    None => match m.name("host") {
-                        Some(host) => host,
-                        None => bail!("no host in URL"),
-                    },
-                },
-            };
+    policy::check(config, dirhost, &target.org)?;
 
-            let org = m.name("org").unwrap().as_str();
-            let repo = m.name("repo").unwrap().as_str();
-            let tail = match m.name("tail") {
-                Some(cap) => cap.as_str().to_string(),
-                None => "".to_string(),
-            };
+    // Config is keyed by the real host.
+    let settings = config.settings(dirhost, &target.org);
+    let ssh_command = settings.ssh_command()?;
+
+    let userinfo = match target.userinfo {
+        Some(ref userinfo) if settings.keep_credentials == Some(true) => Some(userinfo),
+        Some(_) => {
+            eprintln!(
+                "warning: ignoring credentials in URL (set keep_credentials for {dirhost} to use them)"
+            );
+            None
+        }
+        None => None,
+    };
+
+    // Construct the git URL from the form of the spec.
+    let (org, repo) = (target.org.as_str(), target.repo.as_str());
+    let mut giturl = match target.kind {
+        // Explicit SSH: git@host:org/repo.git — preserve as-is
+        Kind::Ssh => format!("git@{}:{}/{}.git", host, org, repo),
+        // Local repo: file:///srv/git/org/repo.git or a plain path — preserve
+        Kind::Local => target.url.clone(),
+        // Explicit URL: https://host/org/repo.git or git://… — preserve
+        Kind::Url(ref prefix) => {
+            let url = format!("{}{}/{}.git", prefix, org, repo);
+            match userinfo {
+                Some(userinfo) => spec::with_userinfo(&url, userinfo),
+                None => url,
+            }
+        }
+        // Shorthand: host/org/repo — built from the host's config if it has
+        // any, else whichever protocol last worked for the host, else the
+        // global protocol (SSH unless configured otherwise)
+        Kind::Shorthand => {
+            let proto = protocol::remembered(host).unwrap_or_else(|| config.protocol());
+            settings.clone_url(host, org, repo, proto)?
+        }
+    };
+
+    // From here on, the names the repo has on disk.
+    let (org, repo) = (target.disk_org.as_str(), target.disk_repo.as_str());
+    let ggroot = &target.root;
+    let topdir = &target.top_dir;
+    let reporoot = &target.repo_dir;
+    let tail = &target.tail;
+    let cd_dir = &target.cd_dir;
 
-            // Construct git URL based on which regex group matched.
-            let giturl = if m.name("git_host").is_some() {
-                // Explicit SSH: git@host:org/repo.git — preserve as-is
-                format!("git@{}:{}/{}.git", host.as_str(), org, repo)
-            } else if m.name("http_host").is_some() {
-                // Explicit HTTPS: https://host/org/repo.git — preserve
-                let prefix = m.name("prefix").unwrap().as_str();
-                format!("{}{}/{}.git", prefix, org, repo)
-            } else {
-                // Shorthand: host/org/repo — use SSH by default, HTTPS if GGHTTP is set
-                let host = host.as_str();
-                if env::var("GGHTTP").is_empty() {
-                    format!("git@{host}:{org}/{repo}.git")
+    // Verify the remote before creating a new top dir (always), or
+    // cloning an org/repo gg hasn't seen (unless turned off), so typos
+    // fail here rather than halfway through the clone.
+    policy::confirm_new_host(config, dirhost)?;
+    let new_top = !topdir.is_dir();
+    let unknown = !reporoot.is_dir() && config.verify(dirhost, org);
+    if (new_top || unknown) && !verify::recently(dirhost, org, repo) {
+        if new_top {
+            eprintln!(
+                "{} \x1b[1m{}\x1b[0m is new, verifying remote...",
+                if topdir.ends_with(dirhost) {
+                    "host dir"
                 } else {
-                    format!("https://{host}/{org}/{repo}.git")
-                }
+                    "dir"
+                },
+                squiggle(topdir.as_path()).display()
+            );
+        } else {
+            eprintln!("{dirhost}/{org}/{repo} is new, verifying remote...");
+        }
+        if remote_exists(&giturl, ssh_command.as_deref(), true) {
+            verify::remember(dirhost, org, repo)?;
+        } else {
+            let fallback = protocol::fallback()?;
+            let alt = match fallback {
+                Fallback::Off => None,
+                _ => alternate(&giturl)
+                    .filter(|(_, _, alt)| remote_exists(alt, ssh_command.as_deref(), false)),
             };
-
-            let hostroot = ggroot.join(host.as_str());
-            if !hostroot.is_dir() {
-                // Host dir doesn't exist — verify the remote repo before creating it.
-                eprintln!(
-                    "host dir \x1b[1m{}\x1b[0m is new, verifying remote...",
-                    squiggle(hostroot.as_path()).display()
-                );
-                let status = Command::new("git")
-                    .args(["ls-remote", &giturl])
-                    .stdout(std::process::Stdio::null())
-                    .stderr(std::process::Stdio::null())
-                    .status();
-                match status {
-                    Ok(s) if s.success() => {
-                        // Repo exists — create the host dir and proceed.
+            match alt {
+                Some((host, proto, alt)) => {
+                    eprintln!("remote only reachable over {proto}: {alt}");
+                    if fallback == Fallback::Remember {
+                        protocol::remember(&host, proto)?;
+                    }
+                    giturl = alt;
+                    verify::remember(dirhost, org, repo)?;
+                }
+                None => {
+                    let mut message = format!("remote not found at {}", spec::redact(&giturl));
+                    if let Some(effective) = rewrites.rewrite(&giturl) {
+                        message += &format!(" (rewritten to {})", spec::redact(&effective));
+                    }
+                    let close = suggestions(config, dirhost, org, repo);
+                    if !close.is_empty() {
+                        message += &format!("\ndid you mean {}?", close.join(" or "));
                     }
-                    _ => {
-                        bail!(
-                            "remote not found at {}\nIf the host is correct, create {} manually",
-                            giturl,
-                            squiggle(hostroot.as_path()).display()
+                    if new_top {
+                        message += &format!(
+                            "\nIf the host is correct, create {} manually",
+                            squiggle(topdir.as_path()).display()
                         );
                     }
+                    bail!(message);
                 }
             }
+        }
+    }
 
-            eprintln!(
-                "👉 \x1b[1;30m{}/\x1b[1;31m{}\x1b[0m/\x1b[1;32m{}\x1b[0m/\x1b[1;34m{}\x1b[0m{}",
-                squiggle(ggroot).display(),
-                host.as_str(),
-                org,
-                repo,
-                tail.as_str()
-            );
+    let banner = layout::render(
+        &target.layout,
+        &format!("\x1b[1;31m{dirhost}\x1b[0m"),
+        &format!("\x1b[1;32m{org}\x1b[0m"),
+        &format!("\x1b[1;34m{repo}\x1b[0m"),
+    )?;
+    eprintln!(
+        "👉 \x1b[1;30m{}/\x1b[0m{}{}",
+        squiggle(ggroot.as_path()).display(),
+        banner.display(),
+        tail.as_str()
+    );
 
-            let orgroot = hostroot.join(org);
-            create_dir_all(orgroot.as_path())?;
+    // Nothing is created on disk here; `gg --apply` creates the org
+    // dir (and host dir) only once a clone succeeds.
+    let orgroot = reporoot
+        .parent()
+        .context("repo dir has no parent")?
+        .to_path_buf();
 
-            if !dry_run {
-                let reporoot = orgroot.join(repo);
-                let mut out = io::stdout();
-                if reporoot.is_dir() {
-                    writeln!(out, "action=fetch")?;
-                    writeln!(out, "git_dir={}", reporoot.display())?;
-                } else {
-                    writeln!(out, "action=clone")?;
-                    writeln!(out, "git_dir={}", orgroot.display())?;
-                    writeln!(out, "git_url={giturl}")?;
-                }
-                writeln!(out, "cd_dir={}{}", reporoot.display(), tail)?;
+    if dry_run {
+        eprintln!("git url: {}", spec::redact(&giturl));
+        if let Some(ref ssh_command) = ssh_command {
+            eprintln!("ssh command: {ssh_command}");
+        }
+        if let Some(effective) = rewrites.rewrite(&giturl) {
+            eprintln!("rewritten by git to: {}", spec::redact(&effective));
+        }
+    } else {
+        // The shell opens cd_dir with viewer, or with file_viewer if
+        // it turns out to be a file; only the clone will tell.
+        let (viewer, file_viewer) = match config.viewer(dirhost, org, editor) {
+            Some(setting) => {
+                let viewer = Viewer::parse(&setting);
+                let file_viewer = match tail.trim_matches('/') {
+                    "" => None,
+                    _ => Some(viewer.file_command(cd_dir, target.line)?),
+                };
+                (Some(viewer.dir_command(cd_dir)?), file_viewer)
+            }
+            None => (None, None),
+        };
+        let landing = landing.unwrap_or(config.landing()).multiplexer();
+        let session = match landing {
+            Some(_) => Some(config.session_name(dirhost, org, repo)?),
+            None => None,
+        };
+        let auto_cd = config.auto_cd();
+        let plan = if reporoot.is_dir() && !incomplete_clone(reporoot.as_path()) {
+            Plan {
+                action: Action::Fetch,
+                host: dirhost.to_string(),
+                org: org.to_string(),
+                repo: repo.to_string(),
+                git_dir: reporoot.clone(),
+                git_url: None,
+                ssh_command,
+                repo_dir: reporoot.clone(),
+                viewer,
+                file_viewer,
+                landing,
+                session: session.clone(),
+                auto_cd,
+                cd_dir: cd_dir.clone(),
+                line: target.line,
             }
+        } else {
+            repos::remember_layouts(config)?;
+            if reporoot.is_dir() {
+                eprintln!(
+                    "{} is an incomplete clone, cloning afresh",
+                    squiggle(reporoot.as_path()).display()
+                );
+            }
+            Plan {
+                action: Action::Clone,
+                host: dirhost.to_string(),
+                org: org.to_string(),
+                repo: repo.to_string(),
+                git_dir: orgroot,
+                git_url: Some(giturl),
+                ssh_command,
+                repo_dir: reporoot.clone(),
+                viewer,
+                file_viewer,
+                landing,
+                session,
+                auto_cd,
+                cd_dir: cd_dir.clone(),
+                line: target.line,
+            }
+        };
+        write!(io::stdout(), "{plan}")?;
+    }
 
-            Ok(())
-        }
-        None => bail!("invalid path: {}", path.display()),
+    Ok(())
+}
+
+/// The host (past any SSH alias), org and repo a clone URL points at, e.g.
+/// a repo's origin.
+pub fn identify(url: &str) -> Option<(String, String, String)> {
+    let m = URL_RE.captures(url)?;
+    let host = host_of(&m)?;
+    for (what, name) in [
+        ("host", host),
+        ("org", m.name("org")?.as_str()),
+        ("repo", m.name("repo")?.as_str()),
+    ] {
+        check_name(what, name).ok()?;
+    }
+    let host = match over_ssh(&m) {
+        true => sshconfig::resolve_alias(host).unwrap_or_else(|| host.to_string()),
+        false => host.to_string(),
+    };
+    Some((
+        host.to_lowercase(),
+        m.name("org")?.as_str().to_string(),
+        m.name("repo")?.as_str().to_string(),
+    ))
+}
+
+/// Reject a host, org or repo name that isn't safe to use as a directory
+/// name or to hand to git and ssh.
+fn check_name(what: &str, name: &str) -> Result<()> {
+    let problem = if name.is_empty() {
+        "is empty"
+    } else if name == "." || name == ".." {
+        "is not a name"
+    } else if name.starts_with('-') {
+        "starts with a dash"
+    } else if name.chars().any(char::is_control) {
+        "contains control characters"
+    } else if name.contains(['/', '\\']) {
+        "contains a path separator"
+    } else {
+        return Ok(());
+    };
+    bail!("invalid {what} {name:?}: {problem}")
+}
+
+/// Reject a path within the repo that could lead out of it.
+fn check_tail(tail: &str) -> Result<()> {
+    if tail.chars().any(char::is_control) {
+        bail!("invalid path {tail:?}: contains control characters");
+    }
+    if Path::new(tail)
+        .components()
+        .any(|c| matches!(c, Component::ParentDir | Component::Prefix(_)))
+    {
+        bail!("invalid path {tail:?}: leads outside the repo");
+    }
+    Ok(())
+}
+
+/// Specs for known repos close to host/org/repo: at the first of host, org
+/// and repo that gg hasn't seen, the names that come closest.
+fn suggestions(config: &Config, host: &str, org: &str, repo: &str) -> Vec<String> {
+    let mut known: Vec<(String, String, String)> = repos::list(config)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|r| (r.host, r.org, r.name))
+        .collect();
+    known.extend(verify::cached());
+
+    let hosts: BTreeSet<&str> = known.iter().map(|k| k.0.as_str()).collect();
+    if !hosts.contains(host) {
+        return suggest::closest(host, hosts)
+            .into_iter()
+            .map(|h| format!("{h}/{org}/{repo}"))
+            .collect();
+    }
+    let orgs: BTreeSet<&str> = known
+        .iter()
+        .filter(|k| k.0 == host)
+        .map(|k| k.1.as_str())
+        .collect();
+    if !orgs.contains(org) {
+        return suggest::closest(org, orgs)
+            .into_iter()
+            .map(|o| format!("{host}/{o}/{repo}"))
+            .collect();
+    }
+    let names: BTreeSet<&str> = known
+        .iter()
+        .filter(|k| k.0 == host && k.1 == org)
+        .map(|k| k.2.as_str())
+        .collect();
+    suggest::closest(repo, names)
+        .into_iter()
+        .map(|r| format!("{host}/{org}/{r}"))
+        .collect()
+}
+
+/// Whether git can list giturl. Unless prompt is set, git won't ask for
+/// credentials, as a guess at the other protocol shouldn't.
+fn remote_exists(giturl: &str, ssh_command: Option<&str>, prompt: bool) -> bool {
+    let mut cmd = Command::new("git");
+    if !prompt {
+        cmd.env("GIT_TERMINAL_PROMPT", "0");
+    }
+    cmd.args(["ls-remote", giturl])
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null());
+    if let Some(ssh_command) = ssh_command {
+        cmd.env("GIT_SSH_COMMAND", ssh_command);
     }
+    let status = cmd.status();
+    matches!(status, Ok(s) if s.success())
 }
@@ -0,0 +1,247 @@
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{bail, Context, Result};
+
+use crate::config::{Config, Section, SectionKind, Settings};
+use crate::env;
+use crate::layout;
+use crate::repos;
+
+const BEGIN: &str = "# --- gg ---";
+const END: &str = "# --- end gg ---";
+
+/// Give repos under each configured host/org their git identity, by writing
+/// an include file per config section and an `includeIf "gitdir:..."` block
+/// in the user's global gitconfig that pulls them in.
+pub fn gitconfig(config: &Config, roots: &[PathBuf], print: bool) -> Result<()> {
+    let includes_dir = env::config_dir()?.join("gitconfig");
+
+    let mut block = format!("{BEGIN}\n# Generated by `gg gitconfig` from gg's config.toml.\n");
+    let mut includes = Vec::new();
+    for section in config.sections() {
+        let Some(content) = identity(section.settings) else {
+            continue;
+        };
+        let include = includes_dir.join(format!("{}.gitconfig", file_stem(&section)));
+        let Some(dir) = section_dir(config, &section)? else {
+            eprintln!(
+                "warning: the layout doesn't keep {} apart from other repos, skipping its identity",
+                section.pattern
+            );
+            continue;
+        };
+        // Where the host ignores case, so must the match: a clone of
+        // github.com/MyCompany/repo is as much mycompany's as any.
+        let (host, org) = host_org(&section);
+        let gitdir = match config.ignore_case(host, org) {
+            true => "gitdir/i",
+            false => "gitdir",
+        };
+        for root in roots {
+            block.push_str(&format!(
+                "[includeIf \"{gitdir}:{}/\"]\n\tpath = {}\n",
+                root.join(&dir).display(),
+                include.display()
+            ));
+        }
+        includes.push((include, content));
+    }
+    block.push_str(END);
+    block.push('\n');
+
+    if print {
+        for (include, content) in &includes {
+            println!("# {}", include.display());
+            print!("{content}");
+            println!();
+        }
+        print!("{block}");
+        return Ok(());
+    }
+
+    // Replace the include files wholesale, so removed sections go away.
+    if includes_dir.is_dir() {
+        fs::remove_dir_all(&includes_dir)?;
+    }
+    if !includes.is_empty() {
+        fs::create_dir_all(&includes_dir)?;
+    }
+    for (include, content) in &includes {
+        fs::write(include, content)?;
+    }
+
+    let global = global_gitconfig()?;
+    let existing = match fs::read_to_string(&global) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e).with_context(|| format!("reading {}", global.display())),
+    };
+    let updated = replace_block(&existing, &block);
+    if updated != existing {
+        fs::write(&global, updated)?;
+    }
+    eprintln!(
+        "{} identit{} configured in {}",
+        includes.len(),
+        if includes.len() == 1 { "y" } else { "ies" },
+        global.display()
+    );
+    Ok(())
+}
+
+/// Report repos whose effective git identity differs from what the config
+/// says it should be.
+pub fn check(config: &Config) -> Result<()> {
+    let mut out = io::stdout();
+    let mut mismatches = 0;
+    for repo in repos::list(config)? {
+        let settings = config.settings(&repo.host, &repo.org);
+        for (key, expected) in [
+            ("user.name", &settings.user_name),
+            ("user.email", &settings.user_email),
+            ("user.signingKey", &settings.signing_key),
+        ] {
+            let Some(expected) = expected else {
+                continue;
+            };
+            let actual = git_config(&repo.path, key)?;
+            if actual.as_deref() != Some(expected.as_str()) {
+                writeln!(
+                    out,
+                    "{}: {key} is {}, expected {expected}",
+                    repo.path.display(),
+                    actual.as_deref().unwrap_or("unset"),
+                )?;
+                mismatches += 1;
+            }
+        }
+    }
+    if mismatches > 0 {
+        bail!("{mismatches} identity mismatches; run `gg gitconfig` to fix");
+    }
+    Ok(())
+}
+
+/// The directory pattern (relative to a root) holding a section's repos,
+/// e.g. github.com/mycompany, or None if the layout mixes them with others.
+fn section_dir(config: &Config, section: &Section) -> Result<Option<String>> {
+    let (host, org) = host_org(section);
+    let layout = config.layout(host, org);
+    let dir = layout::render(&layout, host, org, "*")?
+        .display()
+        .to_string();
+    // Trailing wildcard components add nothing to a gitdir prefix.
+    let mut dir = dir.as_str();
+    while let Some(parent) = dir.strip_suffix("/*") {
+        dir = parent;
+    }
+    Ok((!dir.is_empty() && dir != "*").then(|| dir.to_string()))
+}
+
+/// The host and org a section covers, with * for any org.
+fn host_org<'a>(section: &Section<'a>) -> (&'a str, &'a str) {
+    match section.kind {
+        SectionKind::Host => (section.pattern, "*"),
+        SectionKind::Org => section
+            .pattern
+            .split_once('/')
+            .unwrap_or((section.pattern, "*")),
+    }
+}
+
+/// The include file content for a section's identity settings, if any.
+fn identity(settings: &Settings) -> Option<String> {
+    let mut content = String::new();
+    for (key, value) in [
+        ("name", &settings.user_name),
+        ("email", &settings.user_email),
+        ("signingkey", &settings.signing_key),
+    ] {
+        if let Some(value) = value {
+            content.push_str(&format!("\t{key} = {}\n", quote(value)));
+        }
+    }
+    if content.is_empty() {
+        None
+    } else {
+        Some(format!("[user]\n{content}"))
+    }
+}
+
+/// A gitconfig value in double quotes, so that #, ; and spaces survive.
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// A file name for a section's include file, e.g. org-github.com_mycompany.
+fn file_stem(section: &Section) -> String {
+    let kind = match section.kind {
+        SectionKind::Host => "host",
+        SectionKind::Org => "org",
+    };
+    let name: String = section
+        .pattern
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '.' | '-' => c,
+            '*' => '+',
+            _ => '_',
+        })
+        .collect();
+    format!("{kind}-{name}")
+}
+
+/// Replace gg's block in a gitconfig's content, or append it if there is
+/// none yet.
+fn replace_block(content: &str, block: &str) -> String {
+    if let Some(start) = content.find(BEGIN) {
+        if let Some(end) = content[start..].find(END) {
+            let mut end = start + end + END.len();
+            if content[end..].starts_with('\n') {
+                end += 1;
+            }
+            return format!("{}{block}{}", &content[..start], &content[end..]);
+        }
+    }
+    let mut updated = content.to_string();
+    if !updated.is_empty() && !updated.ends_with('\n') {
+        updated.push('\n');
+    }
+    if !updated.is_empty() {
+        updated.push('\n');
+    }
+    updated.push_str(block);
+    updated
+}
+
+/// The file `git config --global` writes to.
+fn global_gitconfig() -> Result<PathBuf> {
+    if let Ok(path) = std::env::var("GIT_CONFIG_GLOBAL") {
+        return Ok(PathBuf::from(path));
+    }
+    let home = env::home()?.join(".gitconfig");
+    let xdg = match env::var("XDG_CONFIG_HOME").as_str() {
+        "" => env::home()?.join(".config"),
+        dir => PathBuf::from(dir),
+    }
+    .join("git/config");
+    if !home.exists() && xdg.exists() {
+        Ok(xdg)
+    } else {
+        Ok(home)
+    }
+}
+
+fn git_config(repo: &Path, key: &str) -> Result<Option<String>> {
+    let out = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(["config", "--get", key])
+        .output()
+        .context("git not runnable")?;
+    let value = String::from_utf8_lossy(&out.stdout).trim().to_string();
+    Ok(if value.is_empty() { None } else { Some(value) })
+}
@@ -0,0 +1,130 @@
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+
+use crate::config::Config;
+use crate::forge::Web;
+use crate::repos::{self, Repo};
+use crate::spec;
+
+/// What gg knows about the repo a directory is in.
+#[derive(Serialize)]
+struct Here {
+    spec: String,
+    host: String,
+    org: String,
+    repo: String,
+    /// The repo's top dir.
+    path: PathBuf,
+    /// Where dir is within the repo, "" at the top.
+    tail: String,
+    clone_url: Option<String>,
+    web_url: Option<String>,
+}
+
+/// The repo a directory is in, and where the directory is in it.
+pub struct Location {
+    pub repo: Repo,
+    /// Where dir is within the repo, "" at the top.
+    pub tail: String,
+}
+
+/// Work out which repo dir (or file) is in: by where it is under a root if
+/// it's under one, else by its origin.
+pub fn locate(config: &Config, dir: &Path) -> Result<Location> {
+    let dir = dir
+        .canonicalize()
+        .with_context(|| format!("{} not found", dir.display()))?;
+    let probe = match dir.is_dir() {
+        true => dir.as_path(),
+        false => dir.parent().unwrap_or(&dir),
+    };
+    let Some(top) = toplevel(probe) else {
+        bail!("{} is not in a git repo", dir.display());
+    };
+    let repo = identify(config, &top).with_context(|| {
+        format!(
+            "can't tell which repo {} is: it isn't where gg would put one, and has no usable origin",
+            top.display()
+        )
+    })?;
+    let tail = dir
+        .strip_prefix(&top)
+        .map(|tail| tail.display().to_string())
+        .unwrap_or_default();
+    Ok(Location { repo, tail })
+}
+
+/// Print the spec, tail, clone URL and web URL of the repo dir (default the
+/// current directory) is in, as key=value lines or JSON.
+pub fn here(config: &Config, dir: Option<&Path>, json: bool) -> Result<()> {
+    let dir = match dir {
+        Some(dir) => dir.to_path_buf(),
+        None => env::current_dir()?,
+    };
+    let Location { repo, tail } = locate(config, &dir)?;
+    let clone_url = match repos::origin(&repo.path) {
+        Some(origin) => Some(spec::redact(&origin)),
+        None => config
+            .settings(&repo.host, &repo.org)
+            .clone_url(&repo.host, &repo.org, &repo.name, config.protocol())
+            .ok(),
+    };
+    let web = Web::new(config, &repo.host, &repo.org, &repo.name)?;
+    let here = Here {
+        spec: format!("{}/{}/{}", repo.host, repo.org, repo.name),
+        web_url: web.map(|web| web.home().to_string()),
+        host: repo.host,
+        org: repo.org,
+        repo: repo.name,
+        path: repo.path,
+        tail,
+        clone_url,
+    };
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&here)?);
+    } else {
+        println!("spec={}", here.spec);
+        println!("host={}", here.host);
+        println!("org={}", here.org);
+        println!("repo={}", here.repo);
+        println!("path={}", here.path.display());
+        println!("tail={}", here.tail);
+        if let Some(url) = here.clone_url {
+            println!("clone_url={url}");
+        }
+        if let Some(url) = here.web_url {
+            println!("web_url={url}");
+        }
+    }
+    Ok(())
+}
+
+fn identify(config: &Config, top: &Path) -> Option<Repo> {
+    for root in config.roots().ok()? {
+        let root = root.canonicalize().unwrap_or(root);
+        if top.starts_with(&root) && top != root {
+            return repos::identify(config, &root, top.to_path_buf());
+        }
+    }
+    repos::by_origin(top.to_path_buf())
+}
+
+/// The top dir of the git repo dir is in.
+fn toplevel(dir: &Path) -> Option<PathBuf> {
+    let out = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["rev-parse", "--show-toplevel"])
+        .output()
+        .ok()?;
+    if !out.status.success() {
+        return None;
+    }
+    let top = String::from_utf8_lossy(&out.stdout).trim().to_string();
+    Some(PathBuf::from(top))
+}
@@ -0,0 +1,82 @@
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+use anyhow::{bail, Result};
+
+use crate::config::{Config, Settings};
+use crate::env;
+use crate::plan::{Action, Plan};
+
+#[derive(Clone, Copy)]
+pub enum Hook {
+    PostClone,
+    PostFetch,
+    PreCd,
+}
+
+impl Hook {
+    fn name(self) -> &'static str {
+        match self {
+            Hook::PostClone => "post-clone",
+            Hook::PostFetch => "post-fetch",
+            Hook::PreCd => "pre-cd",
+        }
+    }
+
+    fn command(self, settings: &Settings) -> Option<&str> {
+        match self {
+            Hook::PostClone => settings.post_clone.as_deref(),
+            Hook::PostFetch => settings.post_fetch.as_deref(),
+            Hook::PreCd => settings.pre_cd.as_deref(),
+        }
+    }
+}
+
+/// Run a hook for the plan's repo: the executable hooks/<name> in gg's
+/// config dir, then the command the config gives for the repo's host or
+/// org. Both run in the repo with GG_HOST, GG_ORG, GG_REPO, GG_PATH and
+/// GG_ACTION set. A failing hook is reported but only stops gg with
+/// strict_hooks.
+pub fn run(hook: Hook, plan: &Plan, action: Action) -> Result<()> {
+    let config = Config::load()?;
+    let settings = config.settings(&plan.host, &plan.org);
+
+    let mut commands: Vec<(String, Command)> = Vec::new();
+    let file: PathBuf = env::config_dir()?.join("hooks").join(hook.name());
+    if file.is_file() {
+        commands.push((file.display().to_string(), Command::new(&file)));
+    }
+    if let Some(command) = hook.command(&settings) {
+        let mut sh = Command::new("sh");
+        sh.arg("-c").arg(command);
+        commands.push((command.to_string(), sh));
+    }
+
+    for (what, mut cmd) in commands {
+        let status = cmd
+            .current_dir(&plan.repo_dir)
+            .env("GG_HOST", &plan.host)
+            .env("GG_ORG", &plan.org)
+            .env("GG_REPO", &plan.repo)
+            .env("GG_PATH", &plan.repo_dir)
+            .env(
+                "GG_ACTION",
+                match action {
+                    Action::Clone => "clone",
+                    Action::Fetch => "fetch",
+                },
+            )
+            .stdin(Stdio::null())
+            .status();
+        let failure = match status {
+            Ok(s) if s.success() => continue,
+            Ok(s) => format!("{} hook {what} failed ({s})", hook.name()),
+            Err(e) => format!("{} hook {what} not runnable: {e}", hook.name()),
+        };
+        if config.strict_hooks() {
+            bail!(failure);
+        }
+        eprintln!("warning: {failure}");
+    }
+    Ok(())
+}
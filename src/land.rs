@@ -0,0 +1,139 @@
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+
+use crate::env;
+use crate::plan::Plan;
+use crate::template;
+
+/// Where the shell integration leaves you once a repo is ready.
+#[derive(Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Landing {
+    /// cd into the repo and open the viewer.
+    Cd,
+    Tmux,
+    Zellij,
+    /// tmux or zellij when already inside one, else cd.
+    Auto,
+}
+
+/// A terminal multiplexer to land in, in a session named after the repo.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Multiplexer {
+    Tmux,
+    Zellij,
+}
+
+impl Landing {
+    pub fn multiplexer(self) -> Option<Multiplexer> {
+        match self {
+            Landing::Cd => None,
+            Landing::Tmux => Some(Multiplexer::Tmux),
+            Landing::Zellij => Some(Multiplexer::Zellij),
+            Landing::Auto if !env::var("TMUX").is_empty() => Some(Multiplexer::Tmux),
+            Landing::Auto if !env::var("ZELLIJ").is_empty() => Some(Multiplexer::Zellij),
+            Landing::Auto => None,
+        }
+    }
+}
+
+impl Multiplexer {
+    pub fn name(self) -> &'static str {
+        match self {
+            Multiplexer::Tmux => "tmux",
+            Multiplexer::Zellij => "zellij",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Multiplexer> {
+        match s {
+            "tmux" => Some(Multiplexer::Tmux),
+            "zellij" => Some(Multiplexer::Zellij),
+            _ => None,
+        }
+    }
+}
+
+/// The session name template renders to for a repo. tmux won't have . or :
+/// in a name, nor zellij /, so those become _.
+pub fn session_name(template: &str, host: &str, org: &str, repo: &str) -> Result<String> {
+    let name = template::render(template, &[("host", host), ("org", org), ("repo", repo)])?;
+    if name.is_empty() {
+        bail!("session name {template:?} is empty for {host}/{org}/{repo}");
+    }
+    Ok(name.replace(['.', ':', '/'], "_"))
+}
+
+/// Switch to the plan's session, creating it in the repo if need be.
+pub fn land(plan: &str) -> Result<()> {
+    let plan = Plan::parse(plan)?;
+    let multiplexer = plan.landing.context("plan has no landing")?;
+    let session = plan.session.as_deref().context("plan has no session")?;
+    // A file target lands in its directory.
+    let cd_dir = Path::new(&plan.cd_dir);
+    let dir = match cd_dir.is_file() {
+        true => cd_dir.parent().unwrap_or(cd_dir),
+        false => cd_dir,
+    };
+    match multiplexer {
+        Multiplexer::Tmux => tmux(session, dir),
+        Multiplexer::Zellij => zellij(session, dir),
+    }
+}
+
+fn tmux(session: &str, dir: &Path) -> Result<()> {
+    // = makes tmux match the name exactly rather than as a prefix.
+    let target = format!("={session}");
+    let exists = Command::new("tmux")
+        .args(["has-session", "-t", &target])
+        .stderr(Stdio::null())
+        .status()
+        .context("tmux not runnable")?
+        .success();
+    if !exists {
+        run(Command::new("tmux")
+            .args(["new-session", "-d", "-s", session, "-c"])
+            .arg(dir))?;
+    }
+    match env::var("TMUX").as_str() {
+        "" => run(Command::new("tmux").args(["attach-session", "-t", &target])),
+        _ => run(Command::new("tmux").args(["switch-client", "-t", &target])),
+    }
+}
+
+fn zellij(session: &str, dir: &Path) -> Result<()> {
+    if env::var("ZELLIJ").is_empty() {
+        return run(Command::new("zellij")
+            .args(["attach", "--create", session])
+            .current_dir(dir));
+    }
+    // Inside zellij, a tab stands in for the session.
+    let out = Command::new("zellij")
+        .args(["action", "query-tab-names"])
+        .output()
+        .context("zellij not runnable")?;
+    if String::from_utf8_lossy(&out.stdout)
+        .lines()
+        .any(|tab| tab == session)
+    {
+        run(Command::new("zellij").args(["action", "go-to-tab-name", session]))
+    } else {
+        run(Command::new("zellij")
+            .args(["action", "new-tab", "--name", session, "--cwd"])
+            .arg(dir))
+    }
+}
+
+fn run(cmd: &mut Command) -> Result<()> {
+    let program = cmd.get_program().to_string_lossy().to_string();
+    let status = cmd
+        .status()
+        .with_context(|| format!("{program} not runnable"))?;
+    if !status.success() {
+        bail!("{program} failed ({status})");
+    }
+    Ok(())
+}
@@ -0,0 +1,111 @@
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::{bail, Result};
+use regex::Regex;
+
+use crate::template;
+
+/// Where repos go under their root unless configured otherwise.
+pub const DEFAULT: &str = "{host}/{org}/{repo}";
+
+/// The path of a repo relative to its root.
+pub fn render(layout: &str, host: &str, org: &str, repo: &str) -> Result<PathBuf> {
+    let rendered = template::render(layout, &[("host", host), ("org", org), ("repo", repo)])?;
+    let path = PathBuf::from(&rendered);
+    if rendered.is_empty() || !path.components().all(|c| matches!(c, Component::Normal(_))) {
+        bail!("layout {layout:?} gives {rendered:?} for {host}/{org}/{repo}, which is not a relative path");
+    }
+    Ok(path)
+}
+
+/// rel with each component swapped for an existing entry under root that
+/// differs from it only in case, as far down as there are any.
+pub fn match_case(root: &Path, rel: &Path) -> PathBuf {
+    let mut dir = root.to_path_buf();
+    let mut matched = PathBuf::new();
+    for (n, component) in rel.components().enumerate() {
+        let name = component.as_os_str();
+        let lower = name.to_string_lossy().to_lowercase();
+        let entries: Vec<OsString> = match fs::read_dir(&dir) {
+            Ok(entries) => entries
+                .filter_map(|e| e.ok())
+                .map(|e| e.file_name())
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+        let actual = match entries.iter().find(|e| e.as_os_str() == name) {
+            Some(exact) => exact,
+            None => match entries
+                .iter()
+                .find(|e| e.to_string_lossy().to_lowercase() == lower)
+            {
+                Some(other) => other,
+                None => {
+                    matched.extend(rel.components().skip(n));
+                    return matched;
+                }
+            },
+        };
+        dir.push(actual);
+        matched.push(actual);
+    }
+    matched
+}
+
+/// What a layout reveals about the repo at path (relative to its root).
+#[derive(Default)]
+pub struct Parsed {
+    pub host: Option<String>,
+    pub org: Option<String>,
+    pub repo: Option<String>,
+}
+
+/// Recover host, org and repo from a path laid out by layout, as far as the
+/// layout records them. None if the path doesn't fit the layout, or the
+/// layout can't be read backwards (e.g., it uses a placeholder twice).
+pub fn parse(layout: &str, path: &Path) -> Option<Parsed> {
+    let re = Regex::new(&to_regex(layout)?).ok()?;
+    let path = path.to_str()?;
+    let m = re.captures(path)?;
+    let field = |name: &str| m.name(name).map(|m| m.as_str().to_string());
+    Some(Parsed {
+        host: field("host"),
+        org: field("org"),
+        repo: field("repo"),
+    })
+}
+
+/// How many directories deep a layout puts repos.
+pub fn depth(layout: &str) -> usize {
+    layout.split('/').filter(|c| !c.is_empty()).count()
+}
+
+/// A regex matching the paths layout renders, capturing each placeholder.
+fn to_regex(layout: &str) -> Option<String> {
+    let mut re = String::from("^");
+    let mut rest = layout;
+    while let Some(start) = rest.find(['{', '}']) {
+        let (literal, tail) = rest.split_at(start);
+        re.push_str(&regex::escape(literal));
+        if let Some(tail) = tail.strip_prefix("{{") {
+            re.push_str(r"\{");
+            rest = tail;
+        } else if let Some(tail) = tail.strip_prefix("}}") {
+            re.push_str(r"\}");
+            rest = tail;
+        } else {
+            let end = tail.find('}')?;
+            let name = tail[1..end].split(':').next()?;
+            if !matches!(name, "host" | "org" | "repo") {
+                return None;
+            }
+            re.push_str(&format!("(?P<{name}>[^/]+?)"));
+            rest = &tail[end + 1..];
+        }
+    }
+    re.push_str(&regex::escape(rest));
+    re.push('$');
+    Some(re)
+}
@@ -0,0 +1,113 @@
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Context, Result};
+
+use crate::env;
+
+/// How long to wait for another gg before giving up, unless overridden by
+/// GGLOCKTIMEOUT (in seconds).
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(300);
+
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// An advisory lock on a repo dir, held until dropped. Locks live outside the
+/// repo tree, so taking one never creates anything under GGROOT.
+pub struct RepoLock {
+    _file: File,
+}
+
+/// Lock reporoot against other gg processes cloning or fetching it, waiting
+/// (with a message) while another one holds it.
+pub fn lock(reporoot: &Path) -> Result<RepoLock> {
+    let path = lock_path(reporoot)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&path)
+        .with_context(|| format!("opening lock file {}", path.display()))?;
+
+    let timeout = timeout()?;
+    let start = Instant::now();
+    let mut waiting = false;
+    loop {
+        match file.try_lock() {
+            Ok(()) => return Ok(RepoLock { _file: file }),
+            Err(TryLockError::WouldBlock) => {}
+            Err(TryLockError::Error(e)) => {
+                return Err(e).with_context(|| format!("locking {}", path.display()))
+            }
+        }
+        if !waiting {
+            eprintln!(
+                "waiting for another gg working on {}...",
+                reporoot.display()
+            );
+            waiting = true;
+        }
+        if start.elapsed() >= timeout {
+            bail!(
+                "timed out after {}s waiting for another gg working on {}",
+                timeout.as_secs(),
+                reporoot.display()
+            );
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+fn timeout() -> Result<Duration> {
+    match env::var("GGLOCKTIMEOUT").as_str() {
+        "" => Ok(DEFAULT_TIMEOUT),
+        secs => match secs.parse() {
+            Ok(secs) => Ok(Duration::from_secs(secs)),
+            Err(_) => bail!("GGLOCKTIMEOUT must be a number of seconds, not {secs:?}"),
+        },
+    }
+}
+
+/// One lock file per repo dir, named by a hash of its full path, which
+/// stays short however deep the repo is, with the repo's name on the end
+/// for anyone looking.
+fn lock_path(reporoot: &Path) -> Result<PathBuf> {
+    let name: String = reporoot
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default()
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        .take(32)
+        .collect();
+    let name = format!("{}-{name}.lock", id(reporoot));
+    Ok(env::cache_dir()?.join("locks").join(name))
+}
+
+/// A fixed-length name for path, the same for every spelling of it: the
+/// 128-bit FNV-1a hash of its canonical form, in hex.
+pub fn id(path: &Path) -> String {
+    let mut hash: u128 = 0x6c62272e07bb014262b821756295c58d;
+    for byte in canonical(path).as_os_str().as_encoded_bytes() {
+        hash ^= u128::from(*byte);
+        hash = hash.wrapping_mul(0x0000000001000000000000000000013b);
+    }
+    format!("{hash:032x}")
+}
+
+/// path with symlinks and relative parts resolved, as far as it exists.
+fn canonical(path: &Path) -> PathBuf {
+    for base in path.ancestors() {
+        if let Ok(real) = base.canonicalize() {
+            return match path.strip_prefix(base) {
+                Ok(rest) if !rest.as_os_str().is_empty() => real.join(rest),
+                _ => real,
+            };
+        }
+    }
+    path.to_path_buf()
+}
@@ -1,13 +1,39 @@
 // gg will clone or fetch a repo into a standardised location (e.g.,
 // ~/work/github.com/org/repo). It will also cd into it and open it in an IDE.
 
+mod apply;
 mod bash;
+mod browse;
 mod cli;
+mod complete;
+mod config;
+mod configure;
 mod env;
 mod fish;
+mod forge;
 mod getgit;
+mod gitconfig;
+mod here;
+mod hooks;
+mod land;
+mod layout;
+mod lock;
+mod migrate;
+mod pattern;
+mod plan;
+mod policy;
+mod prompt;
+mod protocol;
+mod repos;
+mod rewrite;
 mod setup;
 mod shell;
+mod spec;
+mod sshconfig;
+mod suggest;
+mod template;
+mod verify;
+mod viewer;
 mod zsh;
 
 use std::path::Path;
@@ -15,8 +41,10 @@ use std::path::Path;
 use clap::Parser;
 
 use bash::bash;
+use config::Config;
 use fish::fish;
 use getgit::getgit;
+use land::Landing;
 use zsh::zsh;
 
 use anyhow::Result;
@@ -33,18 +61,63 @@ fn run() -> Result<()> {
 
     let exepath = env::exepath()?;
 
-    let ggroot = match env::var("GGROOT").as_str() {
-        "" => env::home()?.join("work"),
-        var => Path::new(var).to_path_buf(),
-    };
+    if let Some(ref plan) = cli.apply {
+        return apply::apply(plan);
+    }
+    if let Some(ref plan) = cli.land {
+        return land::land(plan);
+    }
+
+    // `gg config` has to work even when the config file doesn't.
+    if let Some(cli::Command::Config { action }) = cli.command {
+        return configure::configure(action);
+    }
+
+    let config = Config::load()?;
+    let roots = config.roots()?;
+
+    if let Some(command) = cli.command {
+        return match command {
+            cli::Command::Config { .. } => unreachable!(),
+            cli::Command::Gitconfig { check: true, .. } => gitconfig::check(&config),
+            cli::Command::Gitconfig { print, .. } => gitconfig::gitconfig(&config, &roots, print),
+            cli::Command::Browse {
+                target,
+                branch,
+                pulls,
+                open,
+            } => {
+                let page = match (branch, pulls) {
+                    (true, _) => browse::Page::Branch,
+                    (_, true) => browse::Page::Pulls,
+                    _ => browse::Page::Path(target),
+                };
+                browse::browse(&config, page, open)
+            }
+            cli::Command::Complete { word, prefix } => {
+                complete::complete(&config, prefix.as_deref(), &word)
+            }
+            cli::Command::Here { dir, json } => here::here(&config, dir.as_deref(), json),
+            cli::Command::Path { spec, exists } => getgit::path(&spec, &config, exists),
+            cli::Command::Prompt { short, origin } => prompt::prompt(&config, short, origin),
+            cli::Command::Migrate { dry_run } => migrate::migrate(&config, dry_run),
+        };
+    }
 
     if cli.get {
         return match cli.target {
             Some(ref path) => getgit(
                 Path::new(path.as_str()),
                 cli.prefix.as_deref(),
+                cli.editor.as_deref(),
+                match (cli.tmux, cli.zellij, cli.cd) {
+                    (true, _, _) => Some(Landing::Tmux),
+                    (_, true, _) => Some(Landing::Zellij),
+                    (_, _, true) => Some(Landing::Cd),
+                    _ => None,
+                },
                 cli.dry_run,
-                ggroot.as_path(),
+                &config,
             ),
             None => {
                 eprintln!("Usage: gg <path>");
@@ -59,9 +132,10 @@ fn run() -> Result<()> {
             let command = cli.target.as_deref();
             let prefix = cli.alias_prefix.as_deref();
             match shell {
-                cli::Shell::Zsh => zsh(command, prefix, &exepath, &ggroot),
-                cli::Shell::Bash => bash(command, prefix, &exepath, &ggroot),
-                cli::Shell::Fish => fish(command, prefix, &exepath, &ggroot),
+                cli::Shell::Zsh => zsh(command, prefix, &exepath, &config.aliases),
+                cli::Shell::Bash => bash(command, prefix, &exepath, &config.aliases),
+                cli::Shell::Fish => fish(command, prefix, &exepath, &config.aliases),
+                cli::Shell::Starship => prompt::starship(&exepath),
             }
         }
     }
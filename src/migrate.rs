@@ -0,0 +1,156 @@
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+use anyhow::{bail, Result};
+
+use crate::config::Config;
+use crate::env;
+use crate::lock;
+use crate::repos;
+
+/// Move every cloned repo to where the config now says it belongs, e.g.
+/// after changing a layout or root. Repos are identified by where they are,
+/// under the layouts now configured or ones used before; their origin only
+/// places those no layout explains. Where the two disagree, the repo is
+/// left for the user to sort out.
+pub fn migrate(config: &Config, dry_run: bool) -> Result<()> {
+    let home = env::home()?;
+    let squiggle = env::squiggler(home.as_path());
+    let mut out = io::stdout();
+    let mut moved = 0;
+    let mut stuck = 0;
+    for (root, path) in repos::paths(config)? {
+        if repos::by_layout(config, &root, &path).is_some() {
+            continue;
+        }
+        let placed = repos::by_previous_layout(&root, &path);
+        let origin = repos::by_origin(path.clone());
+        let repo = match (placed, origin) {
+            (Some(placed), Some(origin)) if !same(config, &placed, &origin) => {
+                eprintln!(
+                    "{} looks like {}/{}/{} but its origin is {}/{}/{}, leaving it",
+                    squiggle(path.as_path()).display(),
+                    placed.host,
+                    placed.org,
+                    placed.name,
+                    origin.host,
+                    origin.org,
+                    origin.name
+                );
+                stuck += 1;
+                continue;
+            }
+            (Some(repo), _) | (None, Some(repo)) => repo,
+            (None, None) => {
+                eprintln!(
+                    "cannot tell which repo {} is, leaving it",
+                    squiggle(path.as_path()).display()
+                );
+                stuck += 1;
+                continue;
+            }
+        };
+        let target = config.repo_dir(&repo.host, &repo.org, &repo.name)?;
+        if target == path {
+            continue;
+        }
+        if target.exists() {
+            eprintln!(
+                "{} already exists, leaving {}",
+                squiggle(target.as_path()).display(),
+                squiggle(path.as_path()).display()
+            );
+            stuck += 1;
+            continue;
+        }
+        writeln!(
+            out,
+            "{} -> {}",
+            squiggle(path.as_path()).display(),
+            squiggle(target.as_path()).display()
+        )?;
+        if !dry_run {
+            let _lock = lock::lock(&path)?;
+            if let Err(e) = relocate(&path, &target) {
+                eprintln!(
+                    "cannot move {} to {}: {e}, leaving it",
+                    squiggle(path.as_path()).display(),
+                    squiggle(target.as_path()).display()
+                );
+                stuck += 1;
+                continue;
+            }
+            prune(&path, &root);
+        }
+        moved += 1;
+    }
+    if !dry_run {
+        repos::remember_layouts(config)?;
+    }
+    match (dry_run, moved) {
+        (_, 0) => eprintln!("all repos are where they belong"),
+        (true, n) => eprintln!("{n} repo(s) would move"),
+        (false, n) => eprintln!("{n} repo(s) moved"),
+    }
+    if stuck > 0 {
+        bail!("{stuck} repo(s) left in place");
+    }
+    Ok(())
+}
+
+/// Whether a and b are the same repo, going by how the host compares names.
+fn same(config: &Config, a: &repos::Repo, b: &repos::Repo) -> bool {
+    let eq = |x: &str, y: &str| match config.ignore_case(&a.host, &a.org) {
+        true => x.eq_ignore_ascii_case(y),
+        false => x == y,
+    };
+    a.host.eq_ignore_ascii_case(&b.host) && eq(&a.org, &b.org) && eq(&a.name, &b.name)
+}
+
+/// Move the repo at from to to, copying it over if they're on different
+/// filesystems (e.g., a root on another volume).
+fn relocate(from: &Path, to: &Path) -> io::Result<()> {
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    match fs::rename(from, to) {
+        Err(e) if e.kind() == io::ErrorKind::CrossesDevices => {
+            if let Err(e) = copy_tree(from, to) {
+                // Leave no half-copied repo behind to block a later run.
+                let _ = fs::remove_dir_all(to);
+                return Err(e);
+            }
+            fs::remove_dir_all(from)
+        }
+        result => result,
+    }
+}
+
+/// Copy the tree at from to to, keeping symlinks as they are and
+/// permissions (hooks must stay executable).
+fn copy_tree(from: &Path, to: &Path) -> io::Result<()> {
+    fs::create_dir(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let (source, dest) = (entry.path(), to.join(entry.file_name()));
+        let kind = entry.file_type()?;
+        if kind.is_symlink() {
+            std::os::unix::fs::symlink(fs::read_link(&source)?, &dest)?;
+        } else if kind.is_dir() {
+            copy_tree(&source, &dest)?;
+        } else {
+            fs::copy(&source, &dest)?;
+        }
+    }
+    fs::set_permissions(to, fs::metadata(from)?.permissions())
+}
+
+/// Remove the dirs a moved repo leaves empty, up to (not including) root.
+fn prune(old: &Path, root: &Path) {
+    for dir in old.ancestors().skip(1) {
+        if dir == root || !dir.starts_with(root) || fs::remove_dir(dir).is_err() {
+            break;
+        }
+    }
+}
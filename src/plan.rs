@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::path::PathBuf;
+
+use anyhow::{bail, Context, Result};
+
+use crate::land::Multiplexer;
+
+/// What the shell integration should do with a repo once getgit has
+/// resolved it.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Clone,
+    Fetch,
+}
+
+/// The key=value protocol between `gg --get` and the shell integration.
+/// The shell reads the keys it needs and hands the whole plan back to
+/// `gg --apply` for the parts gg carries out itself.
+pub struct Plan {
+    pub action: Action,
+    /// The repo, as placed on disk: real host, and org and repo in the case
+    /// already cloned if any.
+    pub host: String,
+    pub org: String,
+    pub repo: String,
+    pub git_dir: PathBuf,
+    pub git_url: Option<String>,
+    /// SSH command for git to use (GIT_SSH_COMMAND) and record in the repo's
+    /// core.sshCommand.
+    pub ssh_command: Option<String>,
+    pub repo_dir: PathBuf,
+    pub cd_dir: String,
+    /// Line to open, from a #L10-style anchor on the spec.
+    pub line: Option<u32>,
+    /// Shell command to open cd_dir with.
+    pub viewer: Option<String>,
+    /// Shell command to open cd_dir with if it's a file, at line.
+    pub file_viewer: Option<String>,
+    /// Multiplexer session to land in instead of cd-ing, via `gg --land`.
+    pub landing: Option<Multiplexer>,
+    pub session: Option<String>,
+    /// Whether the shell should clone/fetch and cd at all.
+    pub auto_cd: bool,
+}
+
+impl fmt::Display for Plan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.action {
+            Action::Clone => writeln!(f, "action=clone")?,
+            Action::Fetch => writeln!(f, "action=fetch")?,
+        }
+        writeln!(f, "host={}", self.host)?;
+        writeln!(f, "org={}", self.org)?;
+        writeln!(f, "repo={}", self.repo)?;
+        writeln!(f, "git_dir={}", self.git_dir.display())?;
+        if let Some(ref url) = self.git_url {
+            writeln!(f, "git_url={url}")?;
+        }
+        if let Some(ref command) = self.ssh_command {
+            writeln!(f, "ssh_command={command}")?;
+        }
+        writeln!(f, "repo_dir={}", self.repo_dir.display())?;
+        writeln!(f, "cd_dir={}", self.cd_dir)?;
+        if let Some(line) = self.line {
+            writeln!(f, "line={line}")?;
+        }
+        if let Some(ref viewer) = self.viewer {
+            writeln!(f, "viewer={viewer}")?;
+        }
+        if let Some(ref viewer) = self.file_viewer {
+            writeln!(f, "file_viewer={viewer}")?;
+        }
+        if let Some(landing) = self.landing {
+            writeln!(f, "landing={}", landing.name())?;
+        }
+        if let Some(ref session) = self.session {
+            writeln!(f, "session={session}")?;
+        }
+        writeln!(f, "auto_cd={}", if self.auto_cd { 1 } else { 0 })
+    }
+}
+
+impl Plan {
+    pub fn parse(text: &str) -> Result<Plan> {
+        let fields: HashMap<&str, &str> = text
+            .lines()
+            .filter_map(|line| line.split_once('='))
+            .collect();
+        let field = |key: &str| {
+            fields
+                .get(key)
+                .copied()
+                .with_context(|| format!("plan is missing {key}"))
+        };
+
+        let action = match field("action")? {
+            "clone" => Action::Clone,
+            "fetch" => Action::Fetch,
+            action => bail!("unknown plan action: {action}"),
+        };
+
+        Ok(Plan {
+            action,
+            host: fields.get("host").unwrap_or(&"").to_string(),
+            org: fields.get("org").unwrap_or(&"").to_string(),
+            repo: fields.get("repo").unwrap_or(&"").to_string(),
+            git_dir: PathBuf::from(field("git_dir")?),
+            git_url: fields.get("git_url").map(|url| url.to_string()),
+            ssh_command: fields.get("ssh_command").map(|cmd| cmd.to_string()),
+            repo_dir: PathBuf::from(field("repo_dir")?),
+            cd_dir: field("cd_dir")?.to_string(),
+            line: fields.get("line").and_then(|line| line.parse().ok()),
+            viewer: fields.get("viewer").map(|viewer| viewer.to_string()),
+            file_viewer: fields.get("file_viewer").map(|viewer| viewer.to_string()),
+            landing: fields.get("landing").and_then(|s| Multiplexer::parse(s)),
+            session: fields.get("session").map(|session| session.to_string()),
+            auto_cd: fields.get("auto_cd") != Some(&"0"),
+        })
+    }
+}
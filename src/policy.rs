@@ -0,0 +1,126 @@
+use std::fs;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+
+use crate::config::Config;
+use crate::env;
+use crate::pattern;
+use crate::repos;
+
+/// Machine-wide allow and deny lists, e.g. set by an IT department. Users
+/// can narrow them in their own config but not widen them.
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+struct Policy {
+    #[serde(default)]
+    allow: Vec<String>,
+    #[serde(default)]
+    deny: Vec<String>,
+}
+
+/// Refuse host/org if the system policy or the user's config denies it or
+/// leaves it off a non-empty allow list. Patterns with a / match host/org,
+/// others just the host.
+pub fn check(config: &Config, host: &str, org: &str) -> Result<()> {
+    let path = system_path();
+    let system = load(&path)?;
+    let source = path.display().to_string();
+    for (allow, deny, source) in [
+        (&system.allow, &system.deny, source.as_str()),
+        (&config.allow, &config.deny, "your config"),
+    ] {
+        let hostorg = format!("{host}/{org}");
+        // Host names never depend on case, and where the host ignores it in
+        // orgs too, neither may the lists: EvilOrg is evilorg. A deny also
+        // holds whatever case the host uses, so it can't be dodged by
+        // turning ignore_case off.
+        let matches = |p: &String, fold: bool| match p.split_once('/') {
+            Some(_) if fold => pattern::matches(&p.to_lowercase(), &hostorg.to_lowercase()),
+            Some((h, o)) => pattern::matches(&format!("{}/{o}", h.to_lowercase()), &hostorg),
+            None => pattern::matches(&p.to_lowercase(), host),
+        };
+        if let Some(p) = deny.iter().find(|p| matches(p, true)) {
+            bail!("{hostorg} is denied by {p:?} in {source}");
+        }
+        let fold = config.ignore_case(host, org);
+        if !allow.is_empty() && !allow.iter().any(|p| matches(p, fold)) {
+            bail!("{hostorg} is not on the allow list in {source}");
+        }
+    }
+    Ok(())
+}
+
+/// Ask before cloning from a host gg has never cloned from, when there's
+/// someone at the terminal to ask, so a mistyped or look-alike host gets a
+/// second look. A host is known once trusted, or if a repo from it is
+/// cloned already (whatever the layout, and leftover empty dirs aside).
+pub fn confirm_new_host(config: &Config, host: &str) -> Result<()> {
+    if !config.confirm_new_hosts() || !io::stdin().is_terminal() || trusted(host) {
+        return Ok(());
+    }
+    if repos::list(config)?.iter().any(|repo| repo.host == host) {
+        // Spare the next run the search.
+        return trust(host);
+    }
+    let mut err = io::stderr();
+    write!(err, "gg has never cloned from {host}. Trust it? [y/N] ")?;
+    err.flush()?;
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+    if !matches!(answer.trim(), "y" | "Y" | "yes") {
+        bail!("not cloning from untrusted host {host}");
+    }
+    trust(host)
+}
+
+fn trusted(host: &str) -> bool {
+    trust_file()
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .is_some_and(|content| content.lines().any(|line| line == host))
+}
+
+fn trust(host: &str) -> Result<()> {
+    let path = trust_file()?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_context(|| format!("opening {}", path.display()))?;
+    writeln!(file, "{host}")?;
+    Ok(())
+}
+
+fn trust_file() -> Result<PathBuf> {
+    Ok(env::state_dir()?.join("trusted_hosts"))
+}
+
+/// The system policy file. Only debug builds, which the tests drive, let
+/// GGTESTPOLICY point elsewhere: in a release build nothing a user sets can
+/// swap the policy for a laxer one.
+fn system_path() -> PathBuf {
+    #[cfg(debug_assertions)]
+    {
+        let path = env::var("GGTESTPOLICY");
+        if !path.is_empty() {
+            return PathBuf::from(path);
+        }
+    }
+    PathBuf::from("/etc/gg/policy.toml")
+}
+
+fn load(path: &Path) -> Result<Policy> {
+    match fs::read_to_string(path) {
+        Ok(content) => {
+            toml::from_str(&content).with_context(|| format!("reading {}", path.display()))
+        }
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Policy::default()),
+        Err(e) => Err(e).with_context(|| format!("reading {}", path.display())),
+    }
+}
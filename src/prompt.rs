@@ -0,0 +1,113 @@
+use std::env;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+
+use crate::config::Config;
+use crate::repos::{self, Repo};
+use crate::shell;
+
+/// Print the repo the current directory is in, for a shell prompt: as
+/// host/org/repo, or with short, with the longest alias prefix it starts
+/// with swapped for alias: (e.g., ghg:org/repo). Outside the roots it
+/// prints nothing, unless origin is set, in which case the repo is
+/// identified by its origin. Only that runs git; the rest is path
+/// arithmetic, cheap enough for every prompt.
+pub fn prompt(config: &Config, short: bool, origin: bool) -> Result<()> {
+    // $PWD keeps the symlinks the user cd'd through, which the roots may
+    // share; the physical path is the fallback.
+    let cwd = env::current_dir()?;
+    let mut dirs = Vec::new();
+    if let Some(pwd) = env::var_os("PWD").map(PathBuf::from) {
+        if pwd.is_absolute() && pwd != cwd && same_dir(&pwd, &cwd) {
+            dirs.push(pwd);
+        }
+    }
+    dirs.push(cwd);
+
+    let roots = config.roots()?;
+    let depth = repos::depth(config);
+    let repo = dirs
+        .iter()
+        .find_map(|dir| under_root(config, &roots, depth, dir))
+        .or_else(|| match origin {
+            true => dirs.iter().find_map(|dir| by_origin(dir)),
+            false => None,
+        });
+    let Some(repo) = repo else {
+        return Ok(());
+    };
+
+    let spec = format!("{}/{}/{}", repo.host, repo.org, repo.name);
+    match short.then(|| abbreviate(config, &spec)).flatten() {
+        Some(short) => println!("{short}"),
+        None => println!("{spec}"),
+    }
+    Ok(())
+}
+
+/// Whether a and b are the same directory, as $PWD may be stale.
+fn same_dir(a: &Path, b: &Path) -> bool {
+    match (a.metadata(), b.metadata()) {
+        (Ok(a), Ok(b)) => a.dev() == b.dev() && a.ino() == b.ino(),
+        _ => false,
+    }
+}
+
+/// The repo dir is in, if it's under a root where a layout puts repos.
+fn under_root(config: &Config, roots: &[PathBuf], depth: usize, dir: &Path) -> Option<Repo> {
+    for root in roots {
+        let Ok(rel) = dir.strip_prefix(root) else {
+            continue;
+        };
+        let mut candidate = root.clone();
+        for component in rel.components().take(depth) {
+            candidate.push(component);
+            if let Some(repo) = repos::by_layout(config, root, &candidate) {
+                return Some(repo);
+            }
+        }
+    }
+    None
+}
+
+/// The repo dir is in, by the origin of the nearest enclosing .git.
+fn by_origin(dir: &Path) -> Option<Repo> {
+    let top = dir.ancestors().find(|dir| dir.join(".git").exists())?;
+    repos::by_origin(top.to_path_buf())
+}
+
+/// spec with the longest alias prefix it starts with swapped for alias:.
+fn abbreviate(config: &Config, spec: &str) -> Option<String> {
+    config
+        .aliases
+        .iter()
+        .filter_map(|(alias, prefix)| {
+            let prefix = prefix.trim_end_matches('/');
+            let rest = spec.strip_prefix(prefix)?;
+            match rest.strip_prefix('/') {
+                Some(rest) => Some((prefix.len(), format!("{alias}:{rest}"))),
+                None if rest.is_empty() => Some((prefix.len(), alias.clone())),
+                None => None,
+            }
+        })
+        .max_by_key(|(len, _)| *len)
+        .map(|(_, short)| short)
+}
+
+/// A starship custom module showing the current repo.
+pub fn starship(exepath: &Path) -> Result<()> {
+    let exepath = shell::escape(&exepath.display().to_string());
+    let command = toml::Value::String(format!("'{exepath}' prompt --short"));
+    print!(
+        "\
+[custom.gg]\n\
+command = {command}\n\
+when = true\n\
+format = \"([$output]($style) )\"\n\
+style = \"bold blue\"\n\
+"
+    );
+    Ok(())
+}
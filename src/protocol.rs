@@ -0,0 +1,153 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::LazyLock;
+
+use anyhow::{bail, Result};
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::env;
+
+#[derive(Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Protocol {
+    Ssh,
+    Https,
+}
+
+impl fmt::Display for Protocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Protocol::Ssh => write!(f, "ssh"),
+            Protocol::Https => write!(f, "https"),
+        }
+    }
+}
+
+impl Protocol {
+    fn parse(s: &str) -> Option<Protocol> {
+        match s {
+            "ssh" => Some(Protocol::Ssh),
+            "https" => Some(Protocol::Https),
+            _ => None,
+        }
+    }
+
+    pub fn url(self, host: &str, org: &str, repo: &str) -> String {
+        match self {
+            Protocol::Ssh => format!("git@{host}:{org}/{repo}.git"),
+            Protocol::Https => format!("https://{host}/{org}/{repo}.git"),
+        }
+    }
+}
+
+/// Whether to retry a failed clone over the other protocol, set via
+/// GGFALLBACK (1 to retry, remember to also record what worked per host).
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Fallback {
+    Off,
+    On,
+    Remember,
+}
+
+pub fn fallback() -> Result<Fallback> {
+    match env::var("GGFALLBACK").as_str() {
+        "" | "0" => Ok(Fallback::Off),
+        "1" => Ok(Fallback::On),
+        "remember" => Ok(Fallback::Remember),
+        other => bail!("GGFALLBACK must be 1 or remember, not {other:?}"),
+    }
+}
+
+static SIMPLE_URL_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?x)
+        ^
+        (?: git@(?P<git_host>[^:]+): | https?://(?P<http_host>[^/@]+)/ )
+        (?P<org>[^/]+) / (?P<repo>[^/]+?) (?: \.git )?
+        $",
+    )
+    .unwrap()
+});
+
+/// The host of url, and the same repo's URL over the other protocol. Only
+/// the plain git@host:org/repo and https://host/org/repo forms gg builds
+/// itself have an obvious counterpart.
+pub fn alternate(url: &str) -> Option<(String, Protocol, String)> {
+    let m = SIMPLE_URL_RE.captures(url)?;
+    let (org, repo) = (&m["org"], &m["repo"]);
+    match (m.name("git_host"), m.name("http_host")) {
+        (Some(host), _) => Some((
+            host.as_str().to_string(),
+            Protocol::Https,
+            Protocol::Https.url(host.as_str(), org, repo),
+        )),
+        (_, Some(host)) => Some((
+            host.as_str().to_string(),
+            Protocol::Ssh,
+            Protocol::Ssh.url(host.as_str(), org, repo),
+        )),
+        _ => None,
+    }
+}
+
+/// Whether git's stderr from a failed clone looks like an authentication or
+/// connection problem, which the other protocol might not have.
+pub fn is_access_error(stderr: &str) -> bool {
+    const SIGNS: &[&str] = &[
+        "permission denied",
+        "could not read from remote repository",
+        "authentication failed",
+        "could not read username",
+        "could not read password",
+        "host key verification failed",
+        "connection refused",
+        "connection timed out",
+        "operation timed out",
+        "could not resolve host",
+        "failed to connect",
+        "ssh: connect to host",
+        "terminal prompts disabled",
+    ];
+    let stderr = stderr.to_lowercase();
+    SIGNS.iter().any(|sign| stderr.contains(sign))
+}
+
+/// The protocol that last worked for host, if GGFALLBACK=remember recorded
+/// one.
+pub fn remembered(host: &str) -> Option<Protocol> {
+    let path = state_file().ok()?;
+    let content = fs::read_to_string(path).ok()?;
+    content.lines().find_map(|line| match line.split_once('=') {
+        Some((h, proto)) if h == host => Protocol::parse(proto),
+        _ => None,
+    })
+}
+
+pub fn remember(host: &str, protocol: Protocol) -> Result<()> {
+    let path = state_file()?;
+    let mut hosts: BTreeMap<String, String> = match fs::read_to_string(&path) {
+        Ok(content) => content
+            .lines()
+            .filter_map(|line| line.split_once('='))
+            .map(|(h, proto)| (h.to_string(), proto.to_string()))
+            .collect(),
+        Err(_) => BTreeMap::new(),
+    };
+    hosts.insert(host.to_string(), protocol.to_string());
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let content: String = hosts
+        .iter()
+        .map(|(h, proto)| format!("{h}={proto}\n"))
+        .collect();
+    fs::write(path, content)?;
+    Ok(())
+}
+
+fn state_file() -> Result<PathBuf> {
+    Ok(env::state_dir()?.join("protocols"))
+}
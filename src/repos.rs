@@ -0,0 +1,239 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::Result;
+
+use crate::config::{Config, SectionKind};
+use crate::env;
+use crate::getgit;
+use crate::layout;
+use crate::pattern;
+
+/// A cloned repo under one of the roots.
+pub struct Repo {
+    pub host: String,
+    pub org: String,
+    pub name: String,
+    pub path: PathBuf,
+}
+
+/// Every repo cloned under the roots, sorted by path within each root.
+/// Hidden entries (including in-progress clones) are skipped, as are repos
+/// whose host, org and name can be told neither from where they are nor
+/// from their origin.
+pub fn list(config: &Config) -> Result<Vec<Repo>> {
+    let mut repos = Vec::new();
+    for (root, path) in paths(config)? {
+        if let Some(repo) = identify(config, &root, path) {
+            repos.push(repo);
+        }
+    }
+    Ok(repos)
+}
+
+/// Every repo dir under the roots, with the root it's under.
+pub fn paths(config: &Config) -> Result<Vec<(PathBuf, PathBuf)>> {
+    let depth = depth(config);
+    let mut paths = Vec::new();
+    for root in config.roots()? {
+        let mut found = Vec::new();
+        find(&root, depth, &mut found);
+        paths.extend(found.into_iter().map(|path| (root.clone(), path)));
+    }
+    Ok(paths)
+}
+
+/// How many directories deep under their roots repos may be.
+pub fn depth(config: &Config) -> usize {
+    layouts(config)
+        .iter()
+        .map(|(_, layout)| layout::depth(layout))
+        .max()
+        .unwrap_or(0)
+        .max(layout::depth(layout::DEFAULT))
+}
+
+/// Work out which repo lives at path under root, going by the layouts in
+/// the config if the path fits one, else by its origin.
+pub fn identify(config: &Config, root: &Path, path: PathBuf) -> Option<Repo> {
+    by_layout(config, root, &path).or_else(|| by_origin(path))
+}
+
+/// Work out which repo would live at path under root from the layouts in
+/// the config alone, without looking at the disk.
+pub fn by_layout(config: &Config, root: &Path, path: &Path) -> Option<Repo> {
+    if let Ok(rel) = path.strip_prefix(root) {
+        for (fixed, layout) in layouts(config) {
+            let Some(parsed) = layout::parse(&layout, rel) else {
+                continue;
+            };
+            let (Some(host), Some(org), Some(name)) = (
+                parsed.host.or_else(|| fixed.0.clone()),
+                parsed.org.or_else(|| fixed.1.clone()),
+                parsed.repo,
+            ) else {
+                continue;
+            };
+            // Only trust the layout if it's the one the repo should be under.
+            if config.repo_dir(&host, &org, &name).ok().as_deref() == Some(path) {
+                return Some(Repo {
+                    host,
+                    org,
+                    name,
+                    path: path.to_path_buf(),
+                });
+            }
+        }
+    }
+    None
+}
+
+/// Identify a repo by its origin URL.
+pub fn by_origin(path: PathBuf) -> Option<Repo> {
+    let (host, org, name) = getgit::identify(&origin(&path)?)?;
+    Some(Repo {
+        host,
+        org,
+        name,
+        path,
+    })
+}
+
+/// The origin URL of the repo at path, if it has one.
+pub fn origin(path: &Path) -> Option<String> {
+    let out = Command::new("git")
+        .arg("-C")
+        .arg(path)
+        .args(["config", "--get", "remote.origin.url"])
+        .output()
+        .ok()?;
+    let url = String::from_utf8_lossy(&out.stdout).trim().to_string();
+    (!url.is_empty()).then_some(url)
+}
+
+/// Work out which repo is at path under root from a layout it may have been
+/// placed by before the config changed: one remembered from a past clone or
+/// migrate, or the default.
+pub fn by_previous_layout(root: &Path, path: &Path) -> Option<Repo> {
+    let rel = path.strip_prefix(root).ok()?;
+    previous_layouts().into_iter().find_map(|(fixed, layout)| {
+        let parsed = layout::parse(&layout, rel)?;
+        let host = parsed.host.or(fixed.0)?;
+        let org = parsed.org.or(fixed.1)?;
+        let name = parsed.repo?;
+        // The layout must lay the repo out just where it is.
+        (layout::render(&layout, &host, &org, &name).ok()?.as_path() == rel).then(|| Repo {
+            host,
+            org,
+            name,
+            path: path.to_path_buf(),
+        })
+    })
+}
+
+/// Note the layouts in use, so that once the config changes, migrate can
+/// still tell which repos they placed where.
+pub fn remember_layouts(config: &Config) -> Result<()> {
+    let path = layouts_file()?;
+    let mut remembered: BTreeSet<(Fixed, String)> = previous_layouts().into_iter().collect();
+    let before = remembered.len();
+    remembered.extend(layouts(config));
+    if remembered.len() == before && path.exists() {
+        return Ok(());
+    }
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let content: String = remembered
+        .iter()
+        .map(|((host, org), layout)| {
+            format!(
+                "{}\t{}\t{layout}\n",
+                host.as_deref().unwrap_or(""),
+                org.as_deref().unwrap_or("")
+            )
+        })
+        .collect();
+    fs::write(path, content)?;
+    Ok(())
+}
+
+/// The remembered layouts, and the default, which needs no remembering.
+fn previous_layouts() -> Vec<(Fixed, String)> {
+    let mut layouts = vec![((None, None), layout::DEFAULT.to_string())];
+    let content = layouts_file()
+        .and_then(|path| Ok(fs::read_to_string(path)?))
+        .unwrap_or_default();
+    let fixed = |s: &str| (!s.is_empty()).then(|| s.to_string());
+    for line in content.lines() {
+        if let [host, org, layout] = line.splitn(3, '\t').collect::<Vec<_>>()[..] {
+            layouts.push(((fixed(host), fixed(org)), layout.to_string()));
+        }
+    }
+    layouts
+}
+
+fn layouts_file() -> Result<PathBuf> {
+    Ok(env::state_dir()?.join("layouts"))
+}
+
+/// What's fixed by the section that configures a layout.
+type Fixed = (Option<String>, Option<String>);
+
+/// Every layout in use, with the host and org its section pins down if the
+/// layout itself may not record them.
+fn layouts(config: &Config) -> Vec<(Fixed, String)> {
+    let mut layouts = vec![(
+        (None, None),
+        config
+            .layout
+            .clone()
+            .unwrap_or_else(|| layout::DEFAULT.to_string()),
+    )];
+    for section in config.sections() {
+        let Some(ref layout) = section.settings.layout else {
+            continue;
+        };
+        let exact = |s: &str| (!pattern::has_wildcards(s)).then(|| s.to_string());
+        let fixed = match section.kind {
+            SectionKind::Host => (exact(section.pattern), None),
+            SectionKind::Org => match section.pattern.split_once('/') {
+                Some((host, org)) => (exact(host), exact(org)),
+                None => (None, None),
+            },
+        };
+        layouts.push((fixed, layout.clone()));
+    }
+    layouts
+}
+
+/// Collect the repos (dirs with a .git) up to depth levels below dir,
+/// without descending into them.
+fn find(dir: &Path, depth: usize, out: &mut Vec<PathBuf>) {
+    if depth == 0 {
+        return;
+    }
+    for path in subdirs(dir) {
+        if path.join(".git").exists() {
+            out.push(path);
+        } else {
+            find(&path, depth - 1, out);
+        }
+    }
+}
+
+fn subdirs(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut dirs: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok())
+        .filter(|e| !e.file_name().to_string_lossy().starts_with('.'))
+        .map(|e| e.path())
+        .filter(|path| path.is_dir())
+        .collect();
+    dirs.sort();
+    dirs
+}
@@ -0,0 +1,72 @@
+use std::process::{Command, Stdio};
+
+/// git's url.<base>.insteadOf rules from the effective gitconfig. git applies
+/// them itself whenever gg hands it a URL, so gg only needs them to tell the
+/// user where a URL really goes and to map a pasted rewritten URL back to
+/// the canonical one that determines the repo's place on disk.
+pub struct Rewrites {
+    /// (instead_of, base) pairs: URLs starting with instead_of are fetched
+    /// from base instead.
+    rules: Vec<(String, String)>,
+}
+
+impl Rewrites {
+    pub fn load() -> Rewrites {
+        let output = Command::new("git")
+            .args(["config", "--get-regexp", r"^url\..*\.insteadof$"])
+            .stderr(Stdio::null())
+            .output();
+        let rules = match output {
+            // Exits with 1 when there are no rules.
+            Ok(out) if out.status.success() => String::from_utf8_lossy(&out.stdout)
+                .lines()
+                .filter_map(|line| {
+                    let (key, instead_of) = line.split_once(' ')?;
+                    let base = key.strip_prefix("url.")?.strip_suffix(".insteadof")?;
+                    Some((instead_of.to_string(), base.to_string()))
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+        Rewrites { rules }
+    }
+
+    /// The URL git will actually use for url, if a rule rewrites it. As in
+    /// git, the longest matching prefix wins.
+    pub fn rewrite(&self, url: &str) -> Option<String> {
+        self.rules
+            .iter()
+            .filter(|(instead_of, _)| url.starts_with(instead_of.as_str()))
+            .max_by_key(|(instead_of, _)| instead_of.len())
+            .map(|(instead_of, base)| format!("{base}{}", &url[instead_of.len()..]))
+    }
+
+    /// The URLs that a rule would rewrite to url, i.e. candidates for its
+    /// canonical form, longest base first.
+    pub fn unrewrite(&self, url: &str) -> Vec<String> {
+        let mut matches: Vec<&(String, String)> = self
+            .rules
+            .iter()
+            .filter(|(instead_of, base)| full_url(instead_of) && url.starts_with(base.as_str()))
+            .collect();
+        matches.sort_by_key(|(_, base)| std::cmp::Reverse(base.len()));
+        matches
+            .into_iter()
+            .map(|(instead_of, base)| format!("{instead_of}{}", &url[base.len()..]))
+            .collect()
+    }
+}
+
+/// Whether s is a whole URL (scheme:// or user@host:) rather than a
+/// shorthand scheme such as gh:, which would only lose the host if put back.
+fn full_url(s: &str) -> bool {
+    if s.contains("://") {
+        return true;
+    }
+    s.split_once('@').is_some_and(|(user, rest)| {
+        !user.contains(['/', ':'])
+            && rest
+                .find(':')
+                .is_some_and(|i| i > 0 && !rest[..i].contains('/'))
+    })
+}
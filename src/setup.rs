@@ -5,6 +5,7 @@ use std::path::Path;
 use anyhow::{bail, Result};
 
 use crate::env;
+use crate::viewer;
 
 pub fn setup(exepath: &Path) -> Result<()> {
     let home = env::home()?;
@@ -37,10 +38,14 @@ pub fn setup(exepath: &Path) -> Result<()> {
     }
 
     // GGDIRVIEWER
-    if vscode_installed() {
+    if let Some(editor) = viewer::detect() {
+        let name = Path::new(&editor)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or(editor);
         let answer = prompt(
             &mut reader,
-            "Open repos in VSCode after clone? [Y/n/-]",
+            &format!("Open repos in {name} after clone? [Y/n/-]"),
             "y",
         )?;
         match answer.as_str() {
@@ -59,7 +64,7 @@ pub fn setup(exepath: &Path) -> Result<()> {
             "-" => {
                 lines.push("export GGDIRVIEWER=-".to_string());
             }
-            _ => {} // default VSCode — no export needed
+            _ => {} // the detected editor is the default — no export needed
         }
     } else {
         let viewer = prompt(
@@ -160,10 +165,6 @@ fn prompt(reader: &mut impl BufRead, question: &str, default: &str) -> Result<St
     }
 }
 
-fn vscode_installed() -> bool {
-    Path::new("/Applications/Visual Studio Code.app/Contents/Resources/app/bin/code").is_file()
-}
-
 fn squiggle_path(home: &Path, path: &Path) -> String {
     match path.strip_prefix(home) {
         Ok(tail) => format!("~/{}", tail.display()),
@@ -3,3 +3,14 @@
 pub fn escape(s: &str) -> String {
     s.replace('\'', "'\\''")
 }
+
+/// A shell word for s: s itself if it needs no quoting, else s in single
+/// quotes.
+pub fn quote(s: &str) -> String {
+    let plain = |c: char| c.is_ascii_alphanumeric() || "%+,-./:=@_".contains(c);
+    if !s.is_empty() && s.chars().all(plain) {
+        s.to_string()
+    } else {
+        format!("'{}'", escape(s))
+    }
+}
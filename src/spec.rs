@@ -0,0 +1,63 @@
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+static USERINFO_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(?P<scheme>[a-z][a-z0-9+.-]*://)(?P<userinfo>[^/@]*)@").unwrap()
+});
+
+static LINE_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^L(?P<line>[0-9]+)").unwrap());
+
+/// A spec with the parts that say nothing about which repo it is taken off.
+pub struct Cleaned {
+    pub spec: String,
+    /// user[:password] from a URL's authority.
+    pub userinfo: Option<String>,
+    /// Line number from a #L10-style anchor.
+    pub line: Option<u32>,
+}
+
+/// Strip a pasted spec's query string, fragment and userinfo, keeping a
+/// line anchor and the userinfo aside.
+pub fn clean(spec: &str) -> Cleaned {
+    let (spec, fragment) = match spec.split_once('#') {
+        Some((spec, fragment)) => (spec, Some(fragment)),
+        None => (spec, None),
+    };
+    let spec = match spec.split_once('?') {
+        Some((spec, _)) => spec,
+        None => spec,
+    };
+    let line = fragment
+        .and_then(|f| LINE_RE.captures(f))
+        .and_then(|m| m["line"].parse().ok());
+    match USERINFO_RE.captures(spec) {
+        Some(m) => Cleaned {
+            spec: format!("{}{}", &m["scheme"], &spec[m.get(0).unwrap().end()..]),
+            userinfo: Some(m["userinfo"].to_string()),
+            line,
+        },
+        None => Cleaned {
+            spec: spec.to_string(),
+            userinfo: None,
+            line,
+        },
+    }
+}
+
+/// url with userinfo put back in after the scheme.
+pub fn with_userinfo(url: &str, userinfo: &str) -> String {
+    match url.split_once("://") {
+        Some((scheme, rest)) => format!("{scheme}://{userinfo}@{rest}"),
+        None => url.to_string(),
+    }
+}
+
+/// url fit for printing. Userinfo is masked whole, since a bare token can
+/// stand in for the user name.
+pub fn redact(url: &str) -> String {
+    match USERINFO_RE.captures(url) {
+        Some(m) => format!("{}***@{}", &m["scheme"], &url[m.get(0).unwrap().end()..]),
+        None => url.to_string(),
+    }
+}
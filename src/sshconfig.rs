@@ -0,0 +1,170 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::env;
+use crate::pattern;
+
+/// ssh gives up on Include chains deeper than this, and so does gg.
+const MAX_INCLUDE_DEPTH: usize = 16;
+
+/// The real host behind an SSH host alias, according to ~/.ssh/config
+/// (following Include directives), e.g. github.com for gh-work given
+///
+/// ```text
+/// Host gh-work
+///     HostName github.com
+///     IdentityFile ~/.ssh/id_work
+/// ```
+///
+/// Returns None if host isn't an alias for anything else.
+pub fn resolve_alias(host: &str) -> Option<String> {
+    let home = env::home().ok()?;
+    let ssh_dir = home.join(".ssh");
+    let mut resolver = Resolver {
+        host,
+        home: &home,
+        ssh_dir: &ssh_dir,
+        hostname: None,
+    };
+    resolver.read(&ssh_dir.join("config"), 0);
+
+    let hostname = resolver.hostname?;
+    let hostname = hostname.replace("%h", host).replace("%%", "%");
+    // An endpoint of the same service (e.g., ssh.github.com for github.com,
+    // as used to get through firewalls) doesn't make host an alias.
+    if hostname.eq_ignore_ascii_case(host) || hostname.ends_with(&format!(".{host}")) {
+        return None;
+    }
+    Some(hostname)
+}
+
+struct Resolver<'a> {
+    host: &'a str,
+    home: &'a Path,
+    ssh_dir: &'a Path,
+    hostname: Option<String>,
+}
+
+impl Resolver<'_> {
+    fn read(&mut self, path: &Path, depth: usize) {
+        if depth > MAX_INCLUDE_DEPTH {
+            return;
+        }
+        let Ok(content) = fs::read_to_string(path) else {
+            return;
+        };
+
+        // Lines before the first Host or Match apply to every host.
+        let mut active = true;
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (keyword, args) = match line.split_once(|c: char| c.is_whitespace() || c == '=') {
+                Some((keyword, args)) => (keyword, args.trim_start_matches([' ', '\t', '='])),
+                None => (line, ""),
+            };
+            let args = split_args(args);
+
+            match keyword.to_ascii_lowercase().as_str() {
+                "host" => active = self.host_matches(&args),
+                // Match conditions depend on more than the host name;
+                // don't guess.
+                "match" => active = false,
+                "include" if active => {
+                    for arg in &args {
+                        for file in self.expand(arg) {
+                            self.read(&file, depth + 1);
+                        }
+                    }
+                }
+                // As in ssh, the first value obtained for a setting wins.
+                "hostname" if active && self.hostname.is_none() => {
+                    self.hostname = args.first().cloned();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Whether host matches a Host line's patterns: at least one positive
+    /// match and no negated one.
+    fn host_matches(&self, patterns: &[String]) -> bool {
+        let mut matched = false;
+        for p in patterns {
+            match p.strip_prefix('!') {
+                Some(negated) if pattern::matches(negated, self.host) => return false,
+                Some(_) => {}
+                None => matched |= pattern::matches(p, self.host),
+            }
+        }
+        matched
+    }
+
+    /// The files an Include argument refers to. Relative paths are relative
+    /// to ~/.ssh, and wildcards expand to matching files in lexical order.
+    fn expand(&self, arg: &str) -> Vec<PathBuf> {
+        let path = match arg.strip_prefix("~/") {
+            Some(rest) => self.home.join(rest),
+            None => self.ssh_dir.join(arg),
+        };
+        let mut files = vec![PathBuf::from("/")];
+        for component in path.components() {
+            let component = component.as_os_str().to_string_lossy();
+            if component == "/" {
+                continue;
+            }
+            if !pattern::has_wildcards(&component) {
+                files.iter_mut().for_each(|f| f.push(component.as_ref()));
+                continue;
+            }
+            let mut expanded = Vec::new();
+            for dir in &files {
+                let Ok(entries) = fs::read_dir(dir) else {
+                    continue;
+                };
+                let mut names: Vec<String> = entries
+                    .filter_map(|e| e.ok())
+                    .map(|e| e.file_name().to_string_lossy().into_owned())
+                    .filter(|name| !name.starts_with('.') && pattern::matches(&component, name))
+                    .collect();
+                names.sort();
+                expanded.extend(names.into_iter().map(|name| dir.join(name)));
+            }
+            files = expanded;
+        }
+        files.retain(|f| f.is_file());
+        files
+    }
+}
+
+/// Split ssh config arguments on whitespace, honouring double quotes.
+fn split_args(args: &str) -> Vec<String> {
+    let mut out = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    let mut any = false;
+    for c in args.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                any = true;
+            }
+            c if c.is_whitespace() && !quoted => {
+                if any {
+                    out.push(std::mem::take(&mut current));
+                    any = false;
+                }
+            }
+            c => {
+                current.push(c);
+                any = true;
+            }
+        }
+    }
+    if any {
+        out.push(current);
+    }
+    out
+}
@@ -0,0 +1,46 @@
+use anyhow::{bail, Result};
+
+/// Expand `{name}` placeholders in template from vars; `{name:lower}`
+/// lowercases the value. `{{` and `}}` stand for literal braces. Unknown or
+/// unterminated placeholders are errors, so typos in config show up instead
+/// of producing odd paths or URLs.
+pub fn render(template: &str, vars: &[(&str, &str)]) -> Result<String> {
+    let mut out = String::with_capacity(template.len());
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                out.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                out.push('}');
+            }
+            '{' => {
+                let mut name = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => name.push(c),
+                        None => bail!("unterminated {{{name} in template {template:?}"),
+                    }
+                }
+                let (key, filter) = match name.split_once(':') {
+                    Some((key, filter)) => (key, Some(filter)),
+                    None => (name.as_str(), None),
+                };
+                let Some((_, v)) = vars.iter().find(|(k, _)| *k == key) else {
+                    bail!("unknown placeholder {{{name}}} in template {template:?}");
+                };
+                match filter {
+                    None => out.push_str(v),
+                    Some("lower") => out.push_str(&v.to_lowercase()),
+                    Some(filter) => bail!("unknown filter :{filter} in template {template:?}"),
+                }
+            }
+            c => out.push(c),
+        }
+    }
+    Ok(out)
+}
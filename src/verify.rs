@@ -0,0 +1,72 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::Result;
+
+use crate::env;
+
+/// How long a successful `git ls-remote` vouches for a repo.
+const TTL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Whether host/org/repo was verified to exist within the TTL.
+pub fn recently(host: &str, org: &str, repo: &str) -> bool {
+    let key = format!("{host}/{org}/{repo}");
+    load().get(&key).is_some_and(|&time| fresh(time))
+}
+
+/// Record that host/org/repo exists, dropping expired entries.
+pub fn remember(host: &str, org: &str, repo: &str) -> Result<()> {
+    let path = cache_file()?;
+    let mut repos = load();
+    repos.retain(|_, time| fresh(*time));
+    repos.insert(format!("{host}/{org}/{repo}"), now());
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let content: String = repos
+        .iter()
+        .map(|(repo, time)| format!("{repo}={time}\n"))
+        .collect();
+    fs::write(path, content)?;
+    Ok(())
+}
+
+/// Every repo verified within the TTL, as (host, org, repo).
+pub fn cached() -> Vec<(String, String, String)> {
+    load()
+        .into_iter()
+        .filter(|(_, time)| fresh(*time))
+        .filter_map(|(key, _)| {
+            let mut parts = key.splitn(3, '/').map(str::to_string);
+            Some((parts.next()?, parts.next()?, parts.next()?))
+        })
+        .collect()
+}
+
+fn load() -> BTreeMap<String, u64> {
+    let Some(content) = cache_file().ok().and_then(|p| fs::read_to_string(p).ok()) else {
+        return BTreeMap::new();
+    };
+    content
+        .lines()
+        .filter_map(|line| line.split_once('='))
+        .filter_map(|(repo, time)| Some((repo.to_string(), time.parse().ok()?)))
+        .collect()
+}
+
+fn fresh(time: u64) -> bool {
+    now().saturating_sub(time) < TTL.as_secs()
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn cache_file() -> Result<PathBuf> {
+    Ok(env::cache_dir()?.join("verified"))
+}
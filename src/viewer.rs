@@ -0,0 +1,155 @@
+use std::env;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+
+use crate::shell;
+use crate::template;
+
+/// An editor gg knows how to point at a directory or at a line in a file.
+struct Known {
+    name: &'static str,
+    /// Arguments to open {dir}.
+    dir: &'static str,
+    /// Arguments to open {file} at {line}.
+    file: &'static str,
+    /// Whether to use it by default if it's on PATH. Terminal editors would
+    /// take over the shell on every gg, so they only run if asked for.
+    detect: bool,
+}
+
+const KNOWN: &[Known] = &[
+    Known {
+        name: "code",
+        dir: "{dir}",
+        file: "--goto {file}:{line}",
+        detect: true,
+    },
+    Known {
+        name: "cursor",
+        dir: "{dir}",
+        file: "--goto {file}:{line}",
+        detect: true,
+    },
+    Known {
+        name: "zed",
+        dir: "{dir}",
+        file: "{file}:{line}",
+        detect: true,
+    },
+    Known {
+        name: "subl",
+        dir: "{dir}",
+        file: "{file}:{line}",
+        detect: true,
+    },
+    Known {
+        name: "idea",
+        dir: "{dir}",
+        file: "--line {line} {file}",
+        detect: true,
+    },
+    Known {
+        name: "nvim",
+        dir: "{dir}",
+        file: "+{line} {file}",
+        detect: false,
+    },
+    Known {
+        name: "vim",
+        dir: "{dir}",
+        file: "+{line} {file}",
+        detect: false,
+    },
+    Known {
+        name: "emacs",
+        dir: "{dir}",
+        file: "+{line} {file}",
+        detect: false,
+    },
+];
+
+/// VSCode's command-line launcher in its macOS app bundle, which isn't on
+/// PATH unless the user puts it there.
+const VSCODE_APP: &str = "/Applications/Visual Studio Code.app/Contents/Resources/app/bin/code";
+
+/// Shell command templates to open a repo directory, or a file in it.
+pub struct Viewer {
+    dir: String,
+    file: String,
+}
+
+impl Viewer {
+    /// The viewer for a setting: an editor gg knows (by name or path), a
+    /// template with {dir}, {file} and {line} placeholders, or any other
+    /// command, which is given the directory or file to open.
+    pub fn parse(setting: &str) -> Viewer {
+        if setting.contains('{') {
+            return Viewer {
+                dir: setting.to_string(),
+                file: setting.to_string(),
+            };
+        }
+        let name = Path::new(setting)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let command = shell::quote(setting);
+        // Literal braces in the command mustn't read as placeholders.
+        let command = command.replace('{', "{{").replace('}', "}}");
+        match KNOWN.iter().find(|known| known.name == name) {
+            Some(known) => Viewer {
+                dir: format!("{command} {}", known.dir),
+                file: format!("{command} {}", known.file),
+            },
+            None => Viewer {
+                dir: format!("{command} {{dir}}"),
+                file: format!("{command} {{file}}"),
+            },
+        }
+    }
+
+    /// The command to open dir.
+    pub fn dir_command(&self, dir: &str) -> Result<String> {
+        render(&self.dir, dir, dir, None)
+    }
+
+    /// The command to open file at line (the top if None).
+    pub fn file_command(&self, file: &str, line: Option<u32>) -> Result<String> {
+        let dir = Path::new(file)
+            .parent()
+            .map(|dir| dir.display().to_string())
+            .unwrap_or_default();
+        render(&self.file, &dir, file, line)
+    }
+}
+
+fn render(template: &str, dir: &str, file: &str, line: Option<u32>) -> Result<String> {
+    template::render(
+        template,
+        &[
+            ("dir", &shell::quote(dir)),
+            ("file", &shell::quote(file)),
+            ("line", &line.unwrap_or(1).to_string()),
+        ],
+    )
+}
+
+/// The editor to use when none is configured: the first GUI editor gg
+/// knows on PATH, else VSCode's macOS launcher if installed.
+pub fn detect() -> Option<String> {
+    let path = env::var_os("PATH").unwrap_or_default();
+    let dirs: Vec<PathBuf> = env::split_paths(&path).collect();
+    for known in KNOWN.iter().filter(|known| known.detect) {
+        if dirs.iter().any(|dir| executable(&dir.join(known.name))) {
+            return Some(known.name.to_string());
+        }
+    }
+    executable(Path::new(VSCODE_APP)).then(|| VSCODE_APP.to_string())
+}
+
+fn executable(path: &Path) -> bool {
+    path.metadata()
+        .is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+}
@@ -1,76 +1,114 @@
+use std::collections::BTreeMap;
 use std::io::{self, Write};
 use std::path::Path;
 
 use anyhow::{Context, Result};
 
+use crate::cli;
 use crate::shell;
 
 pub fn zsh(
     command: Option<&str>,
     prefix: Option<&str>,
     exepath: &Path,
-    ggroot: &Path,
+    aliases: &BTreeMap<String, String>,
 ) -> Result<()> {
     let mut out = io::stdout();
     match command {
         Some(command) => {
             let prefix = prefix.context("missing prefix argument")?;
-            let prefix_path = Path::new(prefix);
-            let prefix_root = ggroot.join(prefix_path);
-
-            let command = shell::escape(command);
-            let prefix_path = shell::escape(&prefix_path.display().to_string());
-            let prefix_root = shell::escape(&prefix_root.display().to_string());
-
-            write!(
-                out,
-                "\
-                    {command}() {{ gg --prefix '{prefix_path}' \"$@\"; }};\n\
-                    _{command}() {{ _path_files -/ -W '{prefix_root}'; }};\n\
-                    compdef _{command} {command};\n\
-                ",
-            )?;
+            alias(&mut out, command, prefix)?;
         }
         None => {
             let exepath = shell::escape(&exepath.display().to_string());
-            let ggroot = shell::escape(&ggroot.display().to_string());
+            let subcommands = cli::subcommands().join("|");
+            let subcommand_words = cli::subcommands().join(" ");
 
             write!(
                 out,
                 "\
 gg() {{\n\
+    case \"${{1:-}}\" in\n\
+        {subcommands}) '{exepath}' \"$@\"; return ;;\n\
+    esac\n\
     local output\n\
     output=$('{exepath}' --get \"$@\") || return $?\n\
     [ -z \"$output\" ] && return\n\
-    local action git_dir git_url cd_dir\n\
+    local action cd_dir viewer file_viewer landing auto_cd\n\
     while IFS= read -r _gg_line; do\n\
         case \"${{_gg_line%%=*}}\" in\n\
             action) action=\"${{_gg_line#*=}}\" ;;\n\
-            git_dir) git_dir=\"${{_gg_line#*=}}\" ;;\n\
-            git_url) git_url=\"${{_gg_line#*=}}\" ;;\n\
             cd_dir) cd_dir=\"${{_gg_line#*=}}\" ;;\n\
+            viewer) viewer=\"${{_gg_line#*=}}\" ;;\n\
+            file_viewer) file_viewer=\"${{_gg_line#*=}}\" ;;\n\
+            landing) landing=\"${{_gg_line#*=}}\" ;;\n\
+            auto_cd) auto_cd=\"${{_gg_line#*=}}\" ;;\n\
         esac\n\
     done <<< \"$output\"\n\
-    if [[ \"${{TERM_PROGRAM:-}}\" == \"vscode\" || -n \"${{GGNOAUTOCD:-}}\" ]]; then return; fi\n\
+    if [ \"$auto_cd\" = 0 ]; then return; fi\n\
     case \"$action\" in\n\
-        clone) git -C \"$git_dir\" clone --recurse-submodules \"$git_url\" || return ;;\n\
-        fetch) git -C \"$git_dir\" fetch --all --prune --jobs=10 --recurse-submodules=yes || return ;;\n\
+        clone|fetch) '{exepath}' --apply \"$output\" || return ;;\n\
     esac\n\
-    cd \"$cd_dir\" || return\n\
-    local viewer=\"${{GGDIRVIEWER:-}}\"\n\
-    if [ -z \"$viewer\" ]; then\n\
-        local vscode='/Applications/Visual Studio Code.app/Contents/Resources/app/bin/code'\n\
-        [ -x \"$vscode\" ] && viewer=\"$vscode\"\n\
-    elif [ \"$viewer\" = \"-\" ]; then\n\
-        viewer=''\n\
+    if [ -n \"$landing\" ]; then '{exepath}' --land \"$output\"; return; fi\n\
+    if [ -f \"$cd_dir\" ]; then\n\
+        cd \"${{cd_dir:h}}\" || return\n\
+        if [ -n \"$file_viewer\" ]; then eval \"$file_viewer\"; fi\n\
+    else\n\
+        cd \"$cd_dir\" || return\n\
+        if [ -n \"$viewer\" ]; then eval \"$viewer\"; fi\n\
+    fi\n\
+}};\n\
+_gg_repos() {{\n\
+    local -a lines partial partial_d full full_d\n\
+    local line value ret=1\n\
+    lines=(\"${{(@f)$('{exepath}' complete ${{1:+--prefix=$1}} -- \"$PREFIX\" 2>/dev/null)}}\")\n\
+    for line in $lines; do\n\
+        value=\"${{line%%$'\\t'*}}\"\n\
+        case \"$value\" in\n\
+            */|*:) partial+=(\"$value\"); partial_d+=(\"$value  -- ${{line#*$'\\t'}}\") ;;\n\
+            *) full+=(\"$value\"); full_d+=(\"$value  -- ${{line#*$'\\t'}}\") ;;\n\
+        esac\n\
+    done\n\
+    compadd -S '' -l -d partial_d -a partial && ret=0\n\
+    compadd -l -d full_d -a full && ret=0\n\
+    return ret\n\
+}};\n\
+_gg() {{\n\
+    if (( CURRENT > 2 )); then\n\
+        case \"$words[2]\" in\n\
+            path) _gg_repos; return ;;\n\
+            {subcommands}) _files; return ;;\n\
+        esac\n\
+    else\n\
+        compadd -- {subcommand_words}\n\
     fi\n\
-    if [ -n \"$viewer\" ]; then \"$viewer\" \"$cd_dir\"; fi\n\
+    _gg_repos\n\
 }};\n\
-_gg() {{ _path_files -/ -W '{ggroot}'; }};\n\
 compdef _gg gg;\n\
+gg_prompt() {{ '{exepath}' prompt \"$@\" 2>/dev/null; }};\n\
 ",
             )?;
+            for (command, prefix) in aliases {
+                alias(&mut out, command, prefix)?;
+            }
         }
     }
     Ok(())
 }
+
+/// Define an alias command that prefixes its argument with prefix.
+/// Completion offers what gg knows of under prefix.
+fn alias(out: &mut impl Write, command: &str, prefix: &str) -> Result<()> {
+    let command = shell::escape(command);
+    let prefix_path = shell::escape(prefix);
+
+    write!(
+        out,
+        "\
+            {command}() {{ gg --prefix '{prefix_path}' \"$@\"; }};\n\
+            _{command}() {{ _gg_repos '{prefix_path}'; }};\n\
+            compdef _{command} {command};\n\
+        ",
+    )?;
+    Ok(())
+}